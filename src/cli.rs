@@ -1,5 +1,9 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use clap::{AppSettings, ArgEnum, Parser};
+
+use crate::invest::{calendar, currency::Currency, sector::SectorKind};
+pub use crate::render::Format;
 
 #[derive(Debug, Parser)]
 #[clap(about, version)]
@@ -7,11 +11,19 @@ pub struct Opts {
     #[clap(short, long)]
     pub debug: bool,
 
+    /// 离线模式 读取本地已同步数据
+    #[clap(long, global = true)]
+    pub offline: bool,
+
+    /// 价格及市值换算为指定币种 仅 info quote replay compare 支持 eg: CNY
+    #[clap(long, global = true, possible_values = ["cny", "hkd", "usd"], ignore_case = true)]
+    pub currency: Option<Currency>,
+
     #[clap(subcommand)]
     pub cmd: Subcommand,
 }
 
-#[derive(Debug, Eq, PartialEq, Parser)]
+#[derive(Debug, PartialEq, Parser)]
 pub enum Subcommand {
     /// 搜索股票
     #[clap(alias = "s")]
@@ -38,12 +50,36 @@ pub enum Subcommand {
         /// 股东结构
         #[clap(short, long)]
         structure: bool,
+        /// 股东变动 对比最近两期十大股东及十大流通股东
+        #[clap(long)]
+        holders_diff: bool,
+        /// 股本结构及限售解禁
+        #[clap(short, long)]
+        capital: bool,
+        /// 在任高管及董监事
+        #[clap(short, long)]
+        managers: bool,
         /// 分红送配
         #[clap(short, long)]
         dividends: bool,
         /// 最新公告
         #[clap(short, long)]
         presses: bool,
+        /// 财务报表
+        #[clap(long, arg_enum)]
+        statements: Option<StatementKind>,
+        /// 财务指标及报表期数
+        #[clap(long, default_value_t = 4)]
+        periods: usize,
+        /// 财务指标仅显示年报
+        #[clap(long, conflicts_with_all = &["quarterly", "ttm"])]
+        annual: bool,
+        /// 财务指标按单季显示
+        #[clap(long, conflicts_with = "ttm")]
+        quarterly: bool,
+        /// 财务指标按滚动四季显示
+        #[clap(long)]
+        ttm: bool,
     },
     /// 行情报价
     #[clap(alias = "q")]
@@ -60,7 +96,228 @@ pub enum Subcommand {
         /// 实时行情多行展示 仅单个时支持
         #[clap(short, long)]
         multiline: bool,
+        /// 实时行情改用 HTTP 轮询 间隔秒数
+        #[clap(short, long)]
+        poll: Option<u64>,
+        /// 实时行情每行展示最近逐笔成交条数
+        #[clap(short, long, default_value_t = 0)]
+        ticks: usize,
+        /// 实时行情逐笔成交追加写入文件 CSV 格式
+        #[clap(long)]
+        tick_file: Option<String>,
+        /// 录制实时行情原始数据到文件 可用 replay 回放
+        #[clap(long)]
+        record: Option<String>,
+        /// 距涨跌停在该百分比以内时标记
+        #[clap(long, default_value_t = 1.0)]
+        near: f64,
+        /// 仅展示涨跌停及接近涨跌停的股票 不支持实时行情
+        #[clap(long, conflicts_with = "realtime")]
+        limits: bool,
+        /// 实时行情休市时不等待开盘
+        #[clap(long)]
+        no_wait: bool,
+    },
+    /// 回放录制的实时行情
+    Replay {
+        /// 录制文件
+        #[clap(required = true)]
+        file: String,
+        /// 回放倍速
+        #[clap(short, long, default_value_t = 1.0)]
+        speed: f64,
+        /// 多行展示 仅单个时支持
+        #[clap(short, long)]
+        multiline: bool,
+        /// 每行展示最近逐笔成交条数
+        #[clap(short, long, default_value_t = 0)]
+        ticks: usize,
+    },
+    /// 财务比率分析
+    Ratios {
+        /// 证券代码 eg: SH601318
+        #[clap(required = true)]
+        symbol: String,
+        /// 期数
+        #[clap(long, default_value_t = 4)]
+        periods: usize,
+        /// 仅显示年报
+        #[clap(long)]
+        annual: bool,
     },
+    /// 历史估值 PE/PB/PS 及分位
+    Valuation {
+        /// 证券代码 eg: SH601318
+        #[clap(required = true)]
+        symbol: String,
+        /// 按月显示历史估值
+        #[clap(short, long)]
+        monthly: bool,
+    },
+    /// 多股对比
+    Compare {
+        /// 证券代码 多个以 , 分隔 或 @自选列表 eg: SH601318,SH601628
+        #[clap(required = true)]
+        symbol: String,
+        /// 排序字段 eg: pe_ttm market_cap
+        #[clap(short, long)]
+        sort: Option<String>,
+        /// 降序
+        #[clap(short, long)]
+        desc: bool,
+        /// 输出格式
+        #[clap(short, long, possible_values = ["table", "json", "csv"], default_value = "table")]
+        format: Format,
+        /// 导出到文件
+        #[clap(short, long)]
+        output: Option<String>,
+    },
+    /// 条件选股 基于全市场行情快照
+    Screen {
+        /// 条件表达式 eg: "pe > 0 && pe < 15 && market_cap > 1e10 && change_pct > 2"
+        ///
+        /// 字段为行情快照字段 price change_pct turnover_rate pe pb market_cap traded_market_cap limit_up 等
+        /// 其中 pe 为动态市盈率 快照不含 pe_ttm
+        #[clap(required = true)]
+        expr: String,
+        /// 排序字段
+        #[clap(short, long, default_value = "change_pct")]
+        sort: String,
+        /// 升序 默认降序
+        #[clap(short, long)]
+        asc: bool,
+        /// 最多显示条数 0 为不限
+        #[clap(short, long, default_value_t = 50)]
+        limit: usize,
+        /// 仅筛选该板块成分股 板块名称或代码 eg: 保险Ⅱ
+        #[clap(long)]
+        sector: Option<String>,
+        /// 输出格式
+        #[clap(short, long, possible_values = ["table", "json", "csv"], default_value = "table")]
+        format: Format,
+        /// 导出到文件
+        #[clap(short, long)]
+        output: Option<String>,
+    },
+    /// 行业及概念板块
+    Sector {
+        #[clap(subcommand)]
+        action: SectorAction,
+    },
+    /// 市场概览 指数 涨跌家数及排行
+    Market {
+        /// 港股
+        #[clap(long, conflicts_with = "us")]
+        hk: bool,
+        /// 美股
+        #[clap(long)]
+        us: bool,
+        /// 排行条数
+        #[clap(short = 'n', long, default_value_t = 10)]
+        top: usize,
+    },
+    /// 各交易所交易时段及开闭市状态
+    MarketStatus,
+    /// 公告列表及下载
+    #[clap(setting = AppSettings::ArgsNegateSubcommands)]
+    Presses {
+        #[clap(subcommand)]
+        action: Option<PressAction>,
+        /// 证券代码 eg: SH601318
+        symbol: Option<String>,
+        /// 起始日期 eg: 2022-01-01
+        #[clap(long, parse(try_from_str = parse_date))]
+        since: Option<NaiveDate>,
+        /// 截止日期 eg: 2022-03-31
+        #[clap(long, parse(try_from_str = parse_date))]
+        until: Option<NaiveDate>,
+        /// 标题关键词 多个以 , 分隔 任一匹配
+        #[clap(short, long, use_delimiter = true)]
+        keywords: Vec<String>,
+        /// 最多获取页数 早于起始日期时提前结束
+        #[clap(short, long, default_value_t = 1)]
+        pages: usize,
+        /// 下载公告 PDF 到指定目录
+        #[clap(short, long)]
+        download: Option<String>,
+        /// 同时保存公告正文纯文本
+        #[clap(long, requires = "download")]
+        text: bool,
+        /// 将下载时解析出的附件链接写入本地数据库 供离线查询
+        #[clap(long, requires = "download")]
+        save: bool,
+    },
+    /// 同步数据到本地
+    Sync {
+        /// 证券代码 多个以 , 分隔 或 @自选列表 eg: SH601318,SZ000001 @default
+        #[clap(required = true)]
+        symbol: String,
+        /// 同时更新所属申万行业及概念板块 需遍历全部板块成分股 较慢 默认保留已同步的
+        #[clap(long)]
+        sectors: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Parser)]
+pub enum SectorAction {
+    /// 板块列表
+    List {
+        /// 板块分类
+        #[clap(short, long, possible_values = ["industry", "sw1", "sw2", "sw3", "concept"], default_value = "sw1")]
+        kind: SectorKind,
+        /// 汇总各板块涨跌 需逐个获取成分股
+        #[clap(short, long)]
+        perf: bool,
+    },
+    /// 板块表现及成分股
+    Show {
+        /// 板块名称或代码 eg: 保险Ⅱ sw2_490200
+        #[clap(required = true)]
+        name: String,
+        /// 排序字段
+        #[clap(short, long, default_value = "change_pct")]
+        sort: String,
+        /// 升序 默认降序
+        #[clap(short, long)]
+        asc: bool,
+        /// 最多显示条数 0 为不限
+        #[clap(short, long, default_value_t = 50)]
+        limit: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Parser)]
+pub enum PressAction {
+    /// 监控新公告 仅提醒首次出现且匹配的
+    Watch {
+        /// 证券代码 多个以 , 分隔 或 @自选列表 eg: @default
+        #[clap(required = true)]
+        symbol: String,
+        /// 标题关键词或分类 多个以 , 分隔 任一匹配 为空时提醒全部 eg: 减持,回购,业绩预告,分红
+        #[clap(short, long, use_delimiter = true)]
+        keywords: Vec<String>,
+        /// 轮询间隔秒数
+        #[clap(short, long, default_value_t = 300)]
+        interval: u64,
+        /// 仅检查一次 适合定时任务
+        #[clap(long)]
+        once: bool,
+    },
+}
+
+/// 财务报表类型
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ArgEnum)]
+pub enum StatementKind {
+    /// 资产负债表
+    Balance,
+    /// 利润表
+    Income,
+    /// 现金流量表
+    Cashflow,
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    calendar::parse_date(date).ok_or_else(|| anyhow!("无效日期 {}，eg: 2022-01-01", date))
 }
 
 impl Opts {
@@ -0,0 +1,413 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::{
+    America::New_York,
+    Asia::{Hong_Kong, Shanghai},
+    Tz,
+};
+use serde::{Deserialize, Deserializer};
+
+use crate::invest::Exchange;
+
+/// 沪深北证休市日 (工作日)
+const HOLIDAYS_CN: &[&str] = &[
+    "2024-01-01",
+    "2024-02-09",
+    "2024-02-12",
+    "2024-02-13",
+    "2024-02-14",
+    "2024-02-15",
+    "2024-02-16",
+    "2024-04-04",
+    "2024-04-05",
+    "2024-05-01",
+    "2024-05-02",
+    "2024-05-03",
+    "2024-06-10",
+    "2024-09-16",
+    "2024-09-17",
+    "2024-10-01",
+    "2024-10-02",
+    "2024-10-03",
+    "2024-10-04",
+    "2024-10-07",
+    "2025-01-01",
+    "2025-01-28",
+    "2025-01-29",
+    "2025-01-30",
+    "2025-01-31",
+    "2025-02-03",
+    "2025-02-04",
+    "2025-04-04",
+    "2025-05-01",
+    "2025-05-02",
+    "2025-05-05",
+    "2025-06-02",
+    "2025-10-01",
+    "2025-10-02",
+    "2025-10-03",
+    "2025-10-06",
+    "2025-10-07",
+    "2025-10-08",
+    "2026-01-01",
+    "2026-01-02",
+    "2026-02-16",
+    "2026-02-17",
+    "2026-02-18",
+    "2026-02-19",
+    "2026-02-20",
+    "2026-02-23",
+    "2026-04-06",
+    "2026-05-01",
+    "2026-05-04",
+    "2026-05-05",
+    "2026-06-19",
+    "2026-09-25",
+    "2026-10-01",
+    "2026-10-02",
+    "2026-10-05",
+    "2026-10-06",
+    "2026-10-07",
+];
+
+/// 港交所休市日
+const HOLIDAYS_HK: &[&str] = &[
+    "2024-01-01",
+    "2024-02-12",
+    "2024-02-13",
+    "2024-03-29",
+    "2024-04-01",
+    "2024-04-04",
+    "2024-05-01",
+    "2024-05-15",
+    "2024-06-10",
+    "2024-07-01",
+    "2024-09-18",
+    "2024-10-01",
+    "2024-10-11",
+    "2024-12-25",
+    "2024-12-26",
+    "2025-01-01",
+    "2025-01-29",
+    "2025-01-30",
+    "2025-01-31",
+    "2025-04-04",
+    "2025-04-18",
+    "2025-04-21",
+    "2025-05-01",
+    "2025-05-05",
+    "2025-07-01",
+    "2025-10-01",
+    "2025-10-07",
+    "2025-10-29",
+    "2025-12-25",
+    "2025-12-26",
+    "2026-01-01",
+    "2026-02-17",
+    "2026-02-18",
+    "2026-02-19",
+    "2026-04-03",
+    "2026-04-06",
+    "2026-04-07",
+    "2026-05-01",
+    "2026-05-25",
+    "2026-06-19",
+    "2026-07-01",
+    "2026-10-01",
+    "2026-10-19",
+    "2026-12-25",
+];
+
+/// 港交所半日市 仅上午交易
+const HALF_DAYS_HK: &[&str] = &[
+    "2024-02-09",
+    "2024-12-24",
+    "2024-12-31",
+    "2025-01-28",
+    "2025-12-24",
+    "2025-12-31",
+    "2026-02-16",
+    "2026-12-24",
+    "2026-12-31",
+];
+
+/// 美股休市日
+const HOLIDAYS_US: &[&str] = &[
+    "2024-01-01",
+    "2024-01-15",
+    "2024-02-19",
+    "2024-03-29",
+    "2024-05-27",
+    "2024-06-19",
+    "2024-07-04",
+    "2024-09-02",
+    "2024-11-28",
+    "2024-12-25",
+    "2025-01-01",
+    "2025-01-09",
+    "2025-01-20",
+    "2025-02-17",
+    "2025-04-18",
+    "2025-05-26",
+    "2025-06-19",
+    "2025-07-04",
+    "2025-09-01",
+    "2025-11-27",
+    "2025-12-25",
+    "2026-01-01",
+    "2026-01-19",
+    "2026-02-16",
+    "2026-04-03",
+    "2026-05-25",
+    "2026-06-19",
+    "2026-07-03",
+    "2026-09-07",
+    "2026-11-26",
+    "2026-12-25",
+];
+
+/// 美股提前收盘日 13:00 收盘
+const HALF_DAYS_US: &[&str] =
+    &["2024-07-03", "2024-11-29", "2024-12-24", "2025-07-03", "2025-11-28", "2025-12-24", "2026-11-27", "2026-12-24"];
+
+/// 节假日表覆盖的年份 超出范围的仅按周末判断
+///
+/// 各表取自交易所公布的休市安排: 沪深北证为国务院办公厅放假通知及上交所休市公告 港交所为其公布的假期表
+/// 美股为 NYSE Holidays & Trading Hours 每年年底交易所公布次年安排后 追加休市日及半日市 并更新此处年份
+pub const YEARS: (i32, i32) = (2024, 2026);
+
+/// date 所在年份的节假日表是否已覆盖
+pub fn is_covered(date: NaiveDate) -> bool {
+    (YEARS.0..=YEARS.1).contains(&date.year())
+}
+
+/// 交易时段
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Phase {
+    /// 休市
+    Closed,
+    /// 开盘集合竞价/盘前竞价
+    OpeningAuction,
+    /// 连续竞价
+    Trading,
+    /// 午间休市
+    Break,
+    /// 收盘集合竞价
+    ClosingAuction,
+    /// 美股盘前
+    PreMarket,
+    /// 美股盘后
+    AfterHours,
+}
+
+impl Phase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Closed => "休市",
+            Phase::OpeningAuction => "开盘集合竞价",
+            Phase::Trading => "交易中",
+            Phase::Break => "午间休市",
+            Phase::ClosingAuction => "收盘集合竞价",
+            Phase::PreMarket => "盘前交易",
+            Phase::AfterHours => "盘后交易",
+        }
+    }
+}
+
+/// 交易日内的时段 交易所当地时间 [start, end)
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Session {
+    pub phase: Phase,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// 交易所当前状态
+#[derive(Debug, Clone)]
+pub struct Status {
+    /// 交易所当地时间
+    pub local: DateTime<Tz>,
+    pub phase: Phase,
+    /// 当日各时段 休市日为空
+    pub sessions: Vec<Session>,
+    /// 下一交易日首个时段开始时间 当日已开始的不计
+    pub next_open: Option<DateTime<Tz>>,
+}
+
+impl Exchange {
+    /// 交易所时区
+    pub fn timezone(&self) -> Tz {
+        match self {
+            Exchange::Sse | Exchange::SZse | Exchange::Bse => Shanghai,
+            Exchange::HKex => Hong_Kong,
+            Exchange::Nyse | Exchange::Nasdaq => New_York,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Exchange::Sse => "上交所",
+            Exchange::SZse => "深交所",
+            Exchange::Bse => "北交所",
+            Exchange::HKex => "港交所",
+            Exchange::Nyse => "纽交所",
+            Exchange::Nasdaq => "纳斯达克",
+        }
+    }
+
+    pub fn all() -> [Exchange; 6] {
+        [Exchange::Sse, Exchange::SZse, Exchange::Bse, Exchange::HKex, Exchange::Nyse, Exchange::Nasdaq]
+    }
+}
+
+/// 是否交易日 date 为交易所当地日期
+pub fn is_trading_day(exchange: &Exchange, date: NaiveDate) -> bool {
+    if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        return false;
+    }
+    !holidays(exchange).contains(&date.format("%Y-%m-%d").to_string().as_str())
+}
+
+/// 交易日各时段 按时间升序 休市日为空
+pub fn sessions(exchange: &Exchange, date: NaiveDate) -> Vec<Session> {
+    if !is_trading_day(exchange, date) {
+        return Vec::new();
+    }
+
+    let date = date.format("%Y-%m-%d").to_string();
+    let spec: &[(Phase, &str, &str)] = match exchange {
+        Exchange::Sse | Exchange::SZse | Exchange::Bse => &[
+            (Phase::OpeningAuction, "09:15", "09:30"),
+            (Phase::Trading, "09:30", "11:30"),
+            (Phase::Break, "11:30", "13:00"),
+            (Phase::Trading, "13:00", "14:57"),
+            (Phase::ClosingAuction, "14:57", "15:00"),
+        ],
+        Exchange::HKex if HALF_DAYS_HK.contains(&date.as_str()) => &[
+            (Phase::OpeningAuction, "09:00", "09:30"),
+            (Phase::Trading, "09:30", "12:00"),
+            (Phase::ClosingAuction, "12:00", "12:10"),
+        ],
+        Exchange::HKex => &[
+            (Phase::OpeningAuction, "09:00", "09:30"),
+            (Phase::Trading, "09:30", "12:00"),
+            (Phase::Break, "12:00", "13:00"),
+            (Phase::Trading, "13:00", "16:00"),
+            (Phase::ClosingAuction, "16:00", "16:10"),
+        ],
+        Exchange::Nyse | Exchange::Nasdaq if HALF_DAYS_US.contains(&date.as_str()) => &[
+            (Phase::PreMarket, "04:00", "09:30"),
+            (Phase::Trading, "09:30", "13:00"),
+            (Phase::AfterHours, "13:00", "17:00"),
+        ],
+        Exchange::Nyse | Exchange::Nasdaq => &[
+            (Phase::PreMarket, "04:00", "09:30"),
+            (Phase::Trading, "09:30", "16:00"),
+            (Phase::AfterHours, "16:00", "20:00"),
+        ],
+    };
+
+    spec.iter()
+        .map(|(phase, start, end)| Session {
+            phase: *phase,
+            start: NaiveTime::parse_from_str(start, "%H:%M").unwrap(),
+            end: NaiveTime::parse_from_str(end, "%H:%M").unwrap(),
+        })
+        .collect()
+}
+
+/// 交易所在 now 时的状态
+pub fn status(exchange: &Exchange, now: DateTime<Utc>) -> Status {
+    let tz = exchange.timezone();
+    let local = now.with_timezone(&tz);
+    let sessions = sessions(exchange, local.date_naive());
+    let phase =
+        sessions.iter().find(|s| s.start <= local.time() && local.time() < s.end).map_or(Phase::Closed, |s| s.phase);
+
+    Status { local, phase, sessions, next_open: next_open(exchange, now) }
+}
+
+/// now 之后首个交易时段的开始时间 30 天内无交易日时返回 None
+pub fn next_open(exchange: &Exchange, now: DateTime<Utc>) -> Option<DateTime<Tz>> {
+    let tz = exchange.timezone();
+    let today = now.with_timezone(&tz).date_naive();
+    (0..30).map(|i| today + Duration::days(i)).find_map(|date| {
+        let first = sessions(exchange, date).into_iter().next()?;
+        let start = tz.from_local_datetime(&date.and_time(first.start)).earliest()?;
+        if start > now {
+            Some(start)
+        } else {
+            None
+        }
+    })
+}
+
+/// 解析日期 支持 2022-03-29 2022/03/29 20220329 无效值(eg: -- )返回 None
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+    let date = date.trim();
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d"].iter().find_map(|f| NaiveDate::parse_from_str(date, f).ok())
+}
+
+/// 解析交易所当地日期时间并附加时区 time 支持 15:00:00 16:00
+pub fn parse_local(exchange: &Exchange, date: &str, time: &str) -> Option<DateTime<FixedOffset>> {
+    let date = parse_date(date)?;
+    let time = ["%H:%M:%S", "%H:%M"].iter().find_map(|f| NaiveTime::parse_from_str(time.trim(), f).ok())?;
+    localize(exchange, date.and_time(time))
+}
+
+/// 交易所当地时间附加时区 夏令时切换时重复的取较早者 不存在的返回 None
+pub fn localize(exchange: &Exchange, datetime: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+    exchange.timezone().from_local_datetime(&datetime).earliest().map(|t| t.fixed_offset())
+}
+
+/// 反序列化可选日期 兼容旧数据中的 -- 等无效值
+pub fn deserialize_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.as_deref().and_then(parse_date))
+}
+
+fn holidays(exchange: &Exchange) -> &'static [&'static str] {
+    match exchange {
+        Exchange::Sse | Exchange::SZse | Exchange::Bse => HOLIDAYS_CN,
+        Exchange::HKex => HOLIDAYS_HK,
+        Exchange::Nyse | Exchange::Nasdaq => HOLIDAYS_US,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let utc = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // 北京时间 2026-10-09 周五 10:00
+        let s = status(&Exchange::Sse, utc("2026-10-09T02:00:00Z"));
+        assert_eq!(s.phase, Phase::Trading);
+        assert_eq!(status(&Exchange::Sse, utc("2026-10-09T04:00:00Z")).phase, Phase::Break);
+        assert_eq!(status(&Exchange::Sse, utc("2026-10-09T06:58:00Z")).phase, Phase::ClosingAuction);
+
+        // 国庆休市 下次开盘 10-08 09:15
+        let s = status(&Exchange::SZse, utc("2026-10-05T02:00:00Z"));
+        assert_eq!(s.phase, Phase::Closed);
+        assert!(s.sessions.is_empty());
+        assert_eq!(s.next_open.unwrap().to_rfc3339(), "2026-10-08T09:15:00+08:00");
+
+        // 纽约夏令时 2026-07-02 10:00 为 UTC 14:00 07-03 休市
+        assert_eq!(status(&Exchange::Nasdaq, utc("2026-07-02T14:00:00Z")).phase, Phase::Trading);
+        let s = status(&Exchange::Nyse, utc("2026-07-03T14:00:00Z"));
+        assert_eq!(s.phase, Phase::Closed);
+        assert_eq!(s.next_open.unwrap().to_rfc3339(), "2026-07-06T04:00:00-04:00");
+
+        // 港股半日市
+        assert_eq!(status(&Exchange::HKex, utc("2026-12-24T05:30:00Z")).phase, Phase::Closed);
+        assert!(is_covered(NaiveDate::from_ymd_opt(YEARS.1, 12, 31).unwrap()));
+        assert!(!is_covered(NaiveDate::from_ymd_opt(YEARS.1 + 1, 1, 1).unwrap()));
+
+        assert_eq!(parse_date("2022/03/29"), NaiveDate::from_ymd_opt(2022, 3, 29));
+        assert_eq!(parse_date(" - "), None);
+        let t = parse_local(&Exchange::HKex, "2022/03/29", "16:00").unwrap();
+        assert_eq!(t.to_rfc3339(), "2022-03-29T16:00:00+08:00");
+        let t = parse_local(&Exchange::Nasdaq, "2022-03-11", "09:30:00").unwrap();
+        assert_eq!(t.to_rfc3339(), "2022-03-11T09:30:00-05:00");
+    }
+}
@@ -0,0 +1,113 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::invest::Exchange;
+
+/// 币种
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    /// 人民币
+    #[default]
+    Cny,
+    /// 港元
+    Hkd,
+    /// 美元
+    Usd,
+}
+
+impl Currency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Currency::Cny => "CNY",
+            Currency::Hkd => "HKD",
+            Currency::Usd => "USD",
+        }
+    }
+
+    /// 由代码识别计价币种 沪市 B 股以美元 深市 B 股以港元计价
+    pub fn from_symbol(symbol: &str) -> Self {
+        let symbol = symbol.to_uppercase();
+        match symbol.get(..2) {
+            _ if symbol.starts_with("SH900") => Currency::Usd,
+            _ if symbol.starts_with("SZ200") => Currency::Hkd,
+            Some(prefix) => Exchange::from_str(prefix).map(|ex| ex.currency()).unwrap_or_default(),
+            None => Currency::default(),
+        }
+    }
+}
+
+impl FromStr for Currency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "CNY" | "RMB" => Ok(Currency::Cny),
+            "HKD" => Ok(Currency::Hkd),
+            "USD" => Ok(Currency::Usd),
+            _ => bail!("不支持的币种：{}", s),
+        }
+    }
+}
+
+impl Exchange {
+    /// 交易所主要计价币种
+    pub fn currency(&self) -> Currency {
+        match self {
+            Exchange::Sse | Exchange::SZse | Exchange::Bse => Currency::Cny,
+            Exchange::HKex => Currency::Hkd,
+            Exchange::Nyse | Exchange::Nasdaq => Currency::Usd,
+        }
+    }
+}
+
+/// 人民币汇率 1 单位外币兑人民币 未知为 0
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rates {
+    pub hkd: f64,
+    pub usd: f64,
+}
+
+impl Rates {
+    /// 1 单位 currency 兑人民币
+    pub fn cny(&self, currency: Currency) -> f64 {
+        match currency {
+            Currency::Cny => 1.0,
+            Currency::Hkd => self.hkd,
+            Currency::Usd => self.usd,
+        }
+    }
+
+    /// 换算金额 汇率未知时返回 None
+    pub fn convert(&self, amount: f64, from: Currency, to: Currency) -> Option<f64> {
+        if from == to {
+            return Some(amount);
+        }
+        match (self.cny(from), self.cny(to)) {
+            (from, to) if from > 0.0 && to > 0.0 => Some(amount * from / to),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(Currency::from_symbol("HK00700"), Currency::Hkd);
+        assert_eq!(Currency::from_symbol("$AAPL"), Currency::Usd);
+        assert_eq!(Currency::from_symbol("SH900901"), Currency::Usd);
+        assert_eq!(Currency::from_symbol("SZ000001"), Currency::Cny);
+        assert_eq!("cny".parse::<Currency>().unwrap(), Currency::Cny);
+
+        let rates = Rates { hkd: 0.9, usd: 7.2 };
+        assert_eq!(rates.convert(100.0, Currency::Hkd, Currency::Cny), Some(90.0));
+        assert_eq!(rates.convert(7.2, Currency::Cny, Currency::Usd), Some(1.0));
+        assert_eq!(rates.convert(8.0, Currency::Usd, Currency::Hkd), Some(64.0));
+        assert_eq!(Rates::default().convert(1.0, Currency::Usd, Currency::Cny), None);
+    }
+}
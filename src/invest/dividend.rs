@@ -0,0 +1,166 @@
+use std::cmp::Reverse;
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::invest::{
+    ratio,
+    stock::{Capital, Dividend, Financial},
+};
+
+/// 年度分红 按所属报告年度汇总 未实施的不计
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct YearDividend {
+    /// 报告年度 含当年中期、季度分红及次年实施的年度分红
+    pub year: i32,
+    /// 每股派息
+    pub dps: f64,
+    /// 每10股送股
+    pub shares_dividend: f64,
+    /// 每10股转增
+    pub shares_into: f64,
+    /// 股利支付率 % 现金分红总额 / 当年年报净利润 分红总额按登记日总股本计 亏损、无年报或无股本时为 0
+    pub payout_ratio: f64,
+}
+
+/// 分红汇总
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    /// 近一年股息率 %
+    pub yield_ttm: f64,
+    /// 最近完整年度股利支付率 %
+    pub payout_ratio: f64,
+    /// 截至最近完整年度连续现金分红年数
+    pub consecutive_years: usize,
+    /// 近 5 个完整年度每股派息复合增长率 % 不足两年或起止为 0 时为 0
+    pub dps_cagr: f64,
+    /// 按年度倒序
+    pub years: Vec<YearDividend>,
+}
+
+/// 分红所属报告年度 页面未提供报告期 按公告日推断
+///
+/// 年度分红于次年 7 月前公告实施 之后公告的为当年中期或季度分红
+pub fn report_year(date: NaiveDate) -> i32 {
+    if date.month() <= 7 {
+        date.year() - 1
+    } else {
+        date.year()
+    }
+}
+
+/// capitals 为股本变动 用于计算现金分红总额
+pub fn by_year(dividends: &[Dividend], financials: &[Financial], capitals: &[Capital]) -> Vec<YearDividend> {
+    let mut years: Vec<(YearDividend, Option<f64>)> = Vec::new();
+    for d in dividends.iter() {
+        let date = match d.date_record.or(d.date_dividend) {
+            Some(date) => date,
+            None => continue,
+        };
+        let year = report_year(d.date);
+        let i = match years.iter().position(|(y, _)| y.year == year) {
+            Some(i) => i,
+            None => {
+                years.push((YearDividend { year, ..Default::default() }, Some(0.0)));
+                years.len() - 1
+            }
+        };
+        let (y, cash) = &mut years[i];
+        y.dps += d.money / 10.0;
+        y.shares_dividend += d.shares_dividend;
+        y.shares_into += d.shares_into;
+        // 登记日的总股本 无股本数据时不计分红总额
+        let shares = capitals.iter().filter(|c| c.date <= date && c.total > 0.0).max_by_key(|c| c.date);
+        *cash = cash.zip(shares).map(|(cash, c)| cash + d.money / 10.0 * c.total);
+    }
+    years.sort_by_key(|(y, _)| Reverse(y.year));
+
+    years
+        .into_iter()
+        .map(|(mut y, cash)| {
+            let annual = financials.iter().find(|f| f.date.year() == y.year && f.date.month() == 12);
+            if let (Some(f), Some(cash)) = (annual.filter(|f| f.net_profit > 0.0), cash) {
+                y.payout_ratio = cash / f.net_profit * 100.0;
+            }
+            y
+        })
+        .collect()
+}
+
+/// today 所属报告年度的分红尚未实施完 不参与连续年数及增长率统计
+pub fn summary(
+    dividends: &[Dividend],
+    financials: &[Financial],
+    capitals: &[Capital],
+    price: f64,
+    today: NaiveDate,
+) -> Summary {
+    let years = by_year(dividends, financials, capitals);
+    let current = report_year(today);
+    let complete = years.iter().filter(|y| y.year < current).collect::<Vec<_>>();
+
+    let mut consecutive_years = 0;
+    for (i, y) in complete.iter().enumerate() {
+        if y.year != current - 1 - i as i32 || y.dps <= 0.0 {
+            break;
+        }
+        consecutive_years += 1;
+    }
+
+    let recent = complete.iter().take_while(|y| y.year > current - 6).collect::<Vec<_>>();
+    let dps_cagr = match (recent.first(), recent.last()) {
+        (Some(latest), Some(earliest)) if latest.year > earliest.year && latest.dps > 0.0 && earliest.dps > 0.0 => {
+            ((latest.dps / earliest.dps).powf(1.0 / (latest.year - earliest.year) as f64) - 1.0) * 100.0
+        }
+        _ => 0.0,
+    };
+
+    Summary {
+        yield_ttm: ratio::dividend_yield(dividends, price, today - Months::new(12)),
+        payout_ratio: complete.first().map_or(0.0, |y| y.payout_ratio),
+        consecutive_years,
+        dps_cagr,
+        years,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::calendar::parse_date;
+
+    #[test]
+    fn test_summary() {
+        let dividend = |date: &str, date_dividend: &str, money: f64| Dividend {
+            date: parse_date(date).unwrap(),
+            date_dividend: parse_date(date_dividend),
+            money,
+            ..Default::default()
+        };
+        let dividends = [
+            dividend("2022-08-26", "--", 5.0),
+            dividend("2022-07-15", "2022-07-25", 15.0),
+            dividend("2021-08-27", "2021-09-03", 8.0),
+            dividend("2021-07-15", "2021-07-22", 12.0),
+            dividend("2020-08-28", "2020-09-04", 8.0),
+            dividend("2020-07-15", "2020-07-22", 8.0),
+            dividend("2019-07-15", "2019-07-22", 10.0),
+            dividend("2017-07-15", "2017-07-22", 5.0),
+        ];
+        let financials =
+            [Financial { date: parse_date("2021-12-31").unwrap(), net_profit: 7.6e9, eps: 5.0, ..Default::default() }];
+        let capital = |date: &str, total: f64| Capital { date: parse_date(date).unwrap(), total, ..Default::default() };
+        let capitals = [capital("2021-12-01", 2e9), capital("2019-01-01", 1e9)];
+
+        let summary = summary(&dividends, &financials, &capitals, 50.0, parse_date("2022-08-01").unwrap());
+        assert_eq!(summary.years.iter().map(|y| y.year).collect::<Vec<_>>(), vec![2021, 2020, 2019, 2018, 2016]);
+        assert_eq!(summary.years[0].dps, 2.3);
+        // 中期按 1e9 股 年度按 2e9 股
+        assert_eq!(summary.payout_ratio, 50.0);
+        assert_eq!(summary.years[1].payout_ratio, 0.0);
+        assert_eq!(summary.consecutive_years, 4);
+        assert!((summary.dps_cagr - 32.0006).abs() < 1e-3);
+        // 2021-09-03 及 2022-07-25 除息
+        assert_eq!(summary.yield_ttm, 4.6);
+    }
+}
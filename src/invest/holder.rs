@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::invest::stock::{Holder, Structure};
+
+/// 股东持股变动
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Change {
+    /// 新进
+    New,
+    /// 退出
+    Exited,
+    /// 增持
+    Increased,
+    /// 减持
+    Decreased,
+    Unchanged,
+}
+
+impl Change {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Change::New => "新进",
+            Change::Exited => "退出",
+            Change::Increased => "增持",
+            Change::Decreased => "减持",
+            Change::Unchanged => "不变",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HolderChange {
+    pub name: String,
+    pub change: Change,
+    /// 本期持股 退出为上期持股
+    pub shares: f64,
+    /// 持股变动数量 退出为负
+    pub delta: f64,
+}
+
+/// 按股东名称对比两期 先按本期顺序 退出的排在最后
+pub fn diff(prev: &[Holder], cur: &[Holder]) -> Vec<HolderChange> {
+    let find = |holders: &[Holder], name: &str| holders.iter().find(|h| h.name.trim() == name.trim()).cloned();
+    let mut changes = cur
+        .iter()
+        .map(|h| {
+            let (change, delta) = match find(prev, &h.name) {
+                None => (Change::New, h.shares),
+                Some(p) if h.shares > p.shares => (Change::Increased, h.shares - p.shares),
+                Some(p) if h.shares < p.shares => (Change::Decreased, h.shares - p.shares),
+                Some(_) => (Change::Unchanged, 0.0),
+            };
+            HolderChange { name: h.name.clone(), change, shares: h.shares, delta }
+        })
+        .collect::<Vec<_>>();
+    changes.extend(prev.iter().filter(|p| find(cur, &p.name).is_none()).map(|p| HolderChange {
+        name: p.name.clone(),
+        change: Change::Exited,
+        shares: p.shares,
+        delta: -p.shares,
+    }));
+
+    changes
+}
+
+/// 股东户数环比变动率 structures 按日期倒序 最早一期为 None
+pub fn holders_num_change(structures: &[Structure]) -> Vec<Option<f64>> {
+    structures
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match structures.get(i + 1) {
+            Some(prev) if prev.holders_num > 0.0 && s.holders_num > 0.0 => {
+                Some((s.holders_num - prev.holders_num) / prev.holders_num * 100.0)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let holder = |name: &str, shares: f64| Holder { name: name.to_string(), shares, ..Default::default() };
+        let prev = vec![holder("A", 100.0), holder("B", 50.0), holder("C", 30.0)];
+        let cur = vec![holder("A", 120.0), holder("D", 60.0), holder("B ", 40.0)];
+        let changes = diff(&prev, &cur).into_iter().map(|c| (c.name, c.change, c.delta)).collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                ("A".to_string(), Change::Increased, 20.0),
+                ("D".to_string(), Change::New, 60.0),
+                ("B ".to_string(), Change::Decreased, -10.0),
+                ("C".to_string(), Change::Exited, -30.0),
+            ]
+        );
+
+        let structure = |holders_num: f64| Structure { holders_num, ..Default::default() };
+        assert_eq!(
+            holders_num_change(&[structure(110.0), structure(100.0), structure(0.0)]),
+            vec![Some(10.0), None, None]
+        );
+    }
+}
@@ -3,8 +3,18 @@ use std::str::FromStr;
 use anyhow::{bail, Error};
 use regex::Regex;
 
+pub mod calendar;
+pub mod currency;
+pub mod dividend;
+pub mod holder;
+pub mod overview;
+pub mod press;
 pub mod quote;
+pub mod ratio;
+pub mod sector;
+pub mod statement;
 pub mod stock;
+pub mod valuation;
 
 #[derive(Debug, Default, Clone)]
 pub struct Investment {
@@ -13,6 +23,8 @@ pub struct Investment {
     pub name: String,
     pub market: Option<Market>,
     pub exchange: Option<Exchange>,
+    /// 所属申万行业及概念板块 同步时由行情中心成分股得到
+    pub sector: Option<sector::Classification>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -31,6 +43,64 @@ pub enum Exchange {
     Nasdaq,
 }
 
+/// 沪深北证股票板块 决定涨跌幅限制
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Board {
+    /// 主板 含 B 股
+    Main,
+    /// 创业板
+    ChiNext,
+    /// 科创板
+    Star,
+    /// 北证
+    Bse,
+}
+
+impl Board {
+    /// 由代码前缀识别 指数 基金等非股票返回 None
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        let symbol = symbol.to_uppercase();
+        let code = symbol.get(2..)?;
+        match symbol.get(..2)? {
+            "SH" if code.starts_with("688") || code.starts_with("689") => Some(Board::Star),
+            "SH" if ["600", "601", "603", "605", "900"].iter().any(|p| code.starts_with(p)) => Some(Board::Main),
+            "SZ" if code.starts_with("300") || code.starts_with("301") => Some(Board::ChiNext),
+            "SZ" if ["000", "001", "002", "003", "200"].iter().any(|p| code.starts_with(p)) => Some(Board::Main),
+            "BJ" if code.starts_with('4') || code.starts_with('8') || code.starts_with("92") => {
+                // 899 为北证指数
+                if code.starts_with("899") {
+                    None
+                } else {
+                    Some(Board::Bse)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// 涨跌幅限制 主板 10% ST 5% 创业板/科创板 20% 北证 30%
+    pub fn limit_rate(&self, st: bool) -> f64 {
+        match self {
+            Board::Main if st => 0.05,
+            Board::Main => 0.1,
+            Board::ChiNext | Board::Star => 0.2,
+            Board::Bse => 0.3,
+        }
+    }
+}
+
+/// 涨跌停价 (涨停, 跌停) 按昨收计算 四舍五入到分 非沪深北证股票或昨收无效时返回 None
+pub fn limit_prices(symbol: &str, name: &str, close: f64) -> Option<(f64, f64)> {
+    let board = Board::from_symbol(symbol)?;
+    if close <= 0.0 {
+        return None;
+    }
+
+    let rate = board.limit_rate(name.contains("ST"));
+    let round = |p: f64| (p * 100.0 + 1e-7).round() / 100.0;
+    Some((round(close * (1.0 + rate)), round(close * (1.0 - rate))))
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Market {
     /// 股票
@@ -83,6 +153,23 @@ impl FromStr for Investment {
     }
 }
 
+impl Investment {
+    /// 所属板块 仅沪深北证股票
+    pub fn board(&self) -> Option<Board> {
+        Board::from_symbol(&self.symbol)
+    }
+
+    /// 风险警示股 名称含 ST
+    pub fn is_st(&self) -> bool {
+        self.name.contains("ST")
+    }
+
+    /// 涨跌幅限制 eg: 0.1
+    pub fn limit_rate(&self) -> Option<f64> {
+        self.board().map(|board| board.limit_rate(self.is_st()))
+    }
+}
+
 /// 美股代码格式 加前缀 $
 pub fn fmt_us_symbol(symbol: &str) -> String {
     if symbol.starts_with('$') {
@@ -91,3 +178,23 @@ pub fn fmt_us_symbol(symbol: &str) -> String {
         "$".to_owned() + symbol
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_prices() {
+        assert_eq!(Board::from_symbol("SH688981"), Some(Board::Star));
+        assert_eq!(Board::from_symbol("SZ300750"), Some(Board::ChiNext));
+        assert_eq!(Board::from_symbol("BJ830799"), Some(Board::Bse));
+        assert_eq!(Board::from_symbol("SH000001"), None);
+        assert_eq!(Board::from_symbol("BJ899050"), None);
+
+        assert_eq!(limit_prices("SH600000", "浦发银行", 7.17), Some((7.89, 6.45)));
+        assert_eq!(limit_prices("SZ000004", "*ST国华", 10.05), Some((10.55, 9.55)));
+        assert_eq!(limit_prices("SZ300001", "ST特锐", 10.0), Some((12.0, 8.0)));
+        assert_eq!(limit_prices("SH688981", "中芯国际", 50.05), Some((60.06, 40.04)));
+        assert_eq!(limit_prices("SZ399001", "深证成指", 10000.0), None);
+    }
+}
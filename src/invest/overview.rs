@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use crate::invest::quote::Snapshot;
+
+/// 市场涨跌统计
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Breadth {
+    /// 有成交的股票数
+    pub total: usize,
+    /// 上涨
+    pub advances: usize,
+    /// 下跌
+    pub declines: usize,
+    /// 平盘
+    pub unchanged: usize,
+    /// 涨停
+    pub limit_up: usize,
+    /// 跌停
+    pub limit_down: usize,
+    /// 总成交额
+    pub volume: f64,
+}
+
+/// 统计涨跌家数 停牌(无成交)的不计入
+pub fn breadth(snapshots: &[Snapshot]) -> Breadth {
+    let mut breadth = Breadth::default();
+    for s in snapshots.iter().filter(|s| s.price > 0.0 && s.turnover > 0.0) {
+        breadth.total += 1;
+        breadth.volume += s.volume;
+        match s.change {
+            c if c > 0.0 => breadth.advances += 1,
+            c if c < 0.0 => breadth.declines += 1,
+            _ => breadth.unchanged += 1,
+        }
+        if s.limit_up > 0.0 && s.price >= s.limit_up {
+            breadth.limit_up += 1;
+        } else if s.limit_down > 0.0 && s.price <= s.limit_down {
+            breadth.limit_down += 1;
+        }
+    }
+
+    breadth
+}
+
+/// 按指标取前 n 个 停牌的不参与排名
+pub fn top(snapshots: &[Snapshot], n: usize, key: fn(&Snapshot) -> f64, desc: bool) -> Vec<Snapshot> {
+    let mut results = snapshots.iter().filter(|s| s.price > 0.0 && s.turnover > 0.0).cloned().collect::<Vec<_>>();
+    results.sort_by(|a, b| {
+        let ord = key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal);
+        if desc {
+            ord.reverse()
+        } else {
+            ord
+        }
+    });
+    results.truncate(n);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::limit_prices;
+
+    #[test]
+    fn test_breadth() {
+        let snapshot = |symbol: &str, name: &str, close: f64, price: f64| {
+            let (limit_up, limit_down) = limit_prices(symbol, name, close).unwrap_or_default();
+            Snapshot {
+                symbol: symbol.to_string(),
+                name: name.to_string(),
+                close,
+                price,
+                change: price - close,
+                turnover: 100.0,
+                volume: price * 100.0,
+                limit_up,
+                limit_down,
+                ..Default::default()
+            }
+        };
+        let snapshots = [
+            snapshot("SH600000", "浦发银行", 7.17, 7.89),
+            snapshot("SZ300750", "宁德时代", 500.0, 400.0),
+            snapshot("SZ000004", "*ST国华", 10.0, 9.5),
+            snapshot("BJ830799", "艾融软件", 20.0, 20.0),
+            Snapshot { turnover: 0.0, ..snapshot("SH600001", "停牌", 10.0, 10.0) },
+        ];
+
+        let b = breadth(&snapshots);
+        assert_eq!((b.total, b.advances, b.declines, b.unchanged), (4, 1, 2, 1));
+        assert_eq!((b.limit_up, b.limit_down), (1, 2));
+
+        let gainers = top(&snapshots, 2, |s| s.change, true);
+        assert_eq!(gainers.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["SH600000", "BJ830799"]);
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::invest::stock::Press;
+
+/// 公告分类 按标题关键词识别
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Category {
+    /// 回购
+    Buyback,
+    /// 减持
+    Reduction,
+    /// 增持
+    Increase,
+    /// 业绩预告 含快报
+    Forecast,
+    /// 分红
+    Dividend,
+    /// 定期报告
+    Periodic,
+    /// 股东大会
+    Meeting,
+    /// 董事会 监事会
+    Board,
+    Other,
+}
+
+impl Category {
+    /// 按顺序匹配 先匹配到的优先 eg: 回购股份用于股权激励 为回购
+    const RULES: [(Category, &'static [&'static str]); 8] = [
+        (Category::Buyback, &["回购"]),
+        (Category::Reduction, &["减持"]),
+        (Category::Increase, &["增持"]),
+        (Category::Forecast, &["业绩预告", "业绩预增", "业绩预减", "业绩预亏", "业绩预盈", "业绩快报"]),
+        (Category::Dividend, &["利润分配", "分红", "权益分派", "派息"]),
+        (Category::Periodic, &["年度报告", "季度报告", "年报", "季报"]),
+        (Category::Meeting, &["股东大会"]),
+        (Category::Board, &["董事会", "监事会"]),
+    ];
+
+    pub fn from_title(title: &str) -> Self {
+        Self::RULES
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|k| title.contains(k)))
+            .map_or(Category::Other, |(category, _)| *category)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Buyback => "回购",
+            Category::Reduction => "减持",
+            Category::Increase => "增持",
+            Category::Forecast => "业绩预告",
+            Category::Dividend => "分红",
+            Category::Periodic => "定期报告",
+            Category::Meeting => "股东大会",
+            Category::Board => "董事会",
+            Category::Other => "其他",
+        }
+    }
+}
+
+impl Press {
+    pub fn category(&self) -> Category {
+        Category::from_title(&self.title)
+    }
+
+    /// 链接中的公告编号 eg: vCB_AllBulletinDetail.php?stockid=601318&id=7891234
+    pub fn id(&self) -> Option<&str> {
+        let (_, query) = self.url.split_once('?')?;
+        query.split('&').find_map(|pair| pair.strip_prefix("id=")).filter(|id| !id.is_empty())
+    }
+}
+
+/// 公告筛选条件 均为空时不过滤
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Filter {
+    /// 起始日期 含当日
+    pub since: Option<NaiveDate>,
+    /// 截止日期 含当日
+    pub until: Option<NaiveDate>,
+    /// 标题关键词或分类名 任一匹配即可
+    pub keywords: Vec<String>,
+}
+
+impl Filter {
+    pub fn matches(&self, press: &Press) -> bool {
+        self.since.is_none_or(|since| press.date >= since)
+            && self.until.is_none_or(|until| press.date <= until)
+            && (self.keywords.is_empty()
+                || self.keywords.iter().any(|k| press.title.contains(k.as_str()) || press.category().as_str() == k))
+    }
+}
+
+/// 保存文件名 eg: 2022-03-29_7891234_中国平安2021年年度报告.pdf 替换路径分隔符等非法字符
+///
+/// 含公告编号 避免同日同名公告重名
+pub fn filename(press: &Press, ext: &str) -> String {
+    let title = press
+        .title
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() || r#"/\:*?"<>|"#.contains(c) { '_' } else { c })
+        .collect::<String>();
+    // 文件名一般不超过 255 字节 中文标题按字符截断
+    let title = title.chars().take(80).collect::<String>();
+    match press.id() {
+        Some(id) => format!("{}_{}_{}.{}", press.date, id, title, ext),
+        None => format!("{}_{}.{}", press.date, title, ext),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::calendar::parse_date;
+
+    #[test]
+    fn test_filter() {
+        let press = Press {
+            date: parse_date("2022-03-29").unwrap(),
+            title: "中国平安:2021年年度报告/摘要".to_string(),
+            ..Default::default()
+        };
+        assert!(Filter::default().matches(&press));
+        let filter = Filter {
+            since: parse_date("2022-01-01"),
+            until: parse_date("2022-03-29"),
+            keywords: vec!["季度".to_string(), "年度".to_string()],
+        };
+        assert!(filter.matches(&press));
+        assert!(!Filter { since: parse_date("2022-03-30"), ..filter.clone() }.matches(&press));
+        assert!(!Filter { keywords: vec!["回购".to_string()], ..filter }.matches(&press));
+
+        let title = |title: &str| Press { title: title.to_string(), ..press.clone() };
+        assert_eq!(title("关于以集中竞价交易方式回购公司A股股份的进展公告").category(), Category::Buyback);
+        assert_eq!(title("2021年年度业绩预告").category(), Category::Forecast);
+        assert_eq!(title("2021年年度A股利润分配方案公告").category(), Category::Dividend);
+        assert_eq!(title("独立董事候选人声明").category(), Category::Other);
+        assert!(Filter { keywords: vec!["分红".to_string()], ..Default::default() }
+            .matches(&title("2021年度权益分派实施公告")));
+
+        assert_eq!(filename(&press, "pdf"), "2022-03-29_中国平安_2021年年度报告_摘要.pdf");
+        let press = Press {
+            url: "https://vip.stock.finance.sina.com.cn/corp/view/vCB_AllBulletinDetail.php?stockid=601318&id=7891234"
+                .to_string(),
+            ..press
+        };
+        assert_eq!(filename(&press, "pdf"), "2022-03-29_7891234_中国平安_2021年年度报告_摘要.pdf");
+    }
+}
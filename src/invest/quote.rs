@@ -1,5 +1,18 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::invest::{
+    currency::{Currency, Rates},
+    limit_prices,
+};
+
 /// 行情报价
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Quote {
     pub symbol: String,
     pub name: String,
@@ -21,7 +34,285 @@ pub struct Quote {
     pub turnover: f64,
     /// 成交额
     pub volume: f64,
-    pub date: String,
+    /// 行情时间 交易所当地时区 无法解析时为 None
+    pub time: Option<DateTime<FixedOffset>>,
+    /// 计价币种
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+impl Quote {
+    /// 涨跌停价 (涨停, 跌停) 非沪深北证股票返回 None
+    pub fn limit_prices(&self) -> Option<(f64, f64)> {
+        limit_prices(&self.symbol, &self.name, self.close)
+    }
+
+    /// 价格及成交额换算为 to 币种 汇率未知时返回 None
+    pub fn convert(&self, rates: &Rates, to: Currency) -> Option<Quote> {
+        let fx = |n: f64| rates.convert(n, self.currency, to);
+        Some(Quote {
+            now: fx(self.now)?,
+            close: fx(self.close)?,
+            open: fx(self.open)?,
+            high: fx(self.high)?,
+            low: fx(self.low)?,
+            buy: fx(self.buy)?,
+            sell: fx(self.sell)?,
+            volume: fx(self.volume)?,
+            currency: to,
+            ..self.clone()
+        })
+    }
+
+    /// 当前价距涨跌停在 near % (按昨收计) 以内时返回状态
+    pub fn limit_status(&self, near: f64) -> Option<LimitStatus> {
+        let (up, down) = self.limit_prices()?;
+        match self.now {
+            p if p <= 0.0 => None,
+            p if p >= up => Some(LimitStatus::LimitUp),
+            p if p <= down => Some(LimitStatus::LimitDown),
+            p if (up - p) / self.close * 100.0 <= near => Some(LimitStatus::NearLimitUp),
+            p if (p - down) / self.close * 100.0 <= near => Some(LimitStatus::NearLimitDown),
+            _ => None,
+        }
+    }
+}
+
+/// 涨跌停状态
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LimitStatus {
+    LimitUp,
+    NearLimitUp,
+    LimitDown,
+    NearLimitDown,
+}
+
+impl LimitStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitStatus::LimitUp => "涨停",
+            LimitStatus::NearLimitUp => "近涨停",
+            LimitStatus::LimitDown => "跌停",
+            LimitStatus::NearLimitDown => "近跌停",
+        }
+    }
+}
+
+/// 全市场行情快照 金额单位元
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub symbol: String,
+    pub name: String,
+    /// 当前价
+    pub price: f64,
+    /// 涨跌额
+    pub change: f64,
+    /// 涨跌幅 %
+    pub change_pct: f64,
+    /// 昨收
+    pub close: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    /// 成交量
+    pub turnover: f64,
+    /// 成交额
+    pub volume: f64,
+    /// 换手率 %
+    pub turnover_rate: f64,
+    /// 市盈率 A 股为动态市盈率 即最近一期利润年化 亏损时为负
+    pub pe: f64,
+    /// 市净率
+    pub pb: f64,
+    /// 总市值
+    pub market_cap: f64,
+    /// 流通市值
+    pub traded_market_cap: f64,
+    /// 涨停价 非沪深北证股票为 0
+    pub limit_up: f64,
+    /// 跌停价
+    pub limit_down: f64,
     pub time: String,
-    // pub currency: String,
+}
+
+/// 日K线
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Bar {
+    /// 交易日 eg: 2022-01-28
+    pub date: NaiveDate,
+    /// 开盘
+    pub open: f64,
+    /// 最高
+    pub high: f64,
+    /// 最低
+    pub low: f64,
+    /// 收盘
+    pub close: f64,
+    /// 成交量
+    pub turnover: f64,
+}
+
+/// 逐笔成交 由相邻两次行情推算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tick {
+    pub symbol: String,
+    /// 交易所当地时区
+    pub time: DateTime<FixedOffset>,
+    /// 成交价
+    pub price: f64,
+    /// 成交量增量
+    pub turnover: f64,
+    /// 成交额增量
+    pub volume: f64,
+    /// 推断的主动方向
+    pub side: Side,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    /// 主动买
+    Buy,
+    /// 主动卖
+    Sell,
+    /// 无法判断
+    Neutral,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::Buy => "B",
+            Side::Sell => "S",
+            Side::Neutral => "N",
+        }
+    }
+}
+
+impl Tick {
+    /// 无新增成交（含跨日成交量归零）或行情时间未知时返回 None
+    pub fn between(prev: &Quote, cur: &Quote) -> Option<Tick> {
+        let turnover = cur.turnover - prev.turnover;
+        if cur.symbol != prev.symbol || turnover <= 0.0 {
+            return None;
+        }
+        let time = cur.time?;
+
+        // 成交价达到上次卖一为主动买 达到买一为主动卖 盘口无效时按价格涨跌判断
+        let quoted = prev.buy > 0.0 && prev.buy < prev.sell;
+        let side = match cur.now {
+            p if quoted && p >= prev.sell => Side::Buy,
+            p if quoted && p <= prev.buy => Side::Sell,
+            p if p > prev.now => Side::Buy,
+            p if p < prev.now => Side::Sell,
+            _ => Side::Neutral,
+        };
+
+        Some(Tick {
+            symbol: cur.symbol.to_string(),
+            time,
+            price: cur.now,
+            turnover,
+            volume: cur.volume - prev.volume,
+            side,
+        })
+    }
+}
+
+/// 各代码最近的逐笔成交
+#[derive(Debug, Default)]
+pub struct TickLog {
+    capacity: usize,
+    quotes: HashMap<String, Quote>,
+    ticks: HashMap<String, VecDeque<Tick>>,
+}
+
+impl TickLog {
+    /// capacity: 每个代码保留的条数
+    pub fn new(capacity: usize) -> Self {
+        TickLog { capacity, ..Default::default() }
+    }
+
+    /// 记录最新行情 返回推算出的逐笔成交
+    pub fn push(&mut self, quote: &Quote) -> Option<Tick> {
+        let tick = self.quotes.get(&quote.symbol).and_then(|prev| Tick::between(prev, quote));
+        self.quotes.insert(quote.symbol.to_string(), quote.clone());
+        if let Some(tick) = &tick {
+            let ticks = self.ticks.entry(quote.symbol.to_string()).or_default();
+            ticks.push_back(tick.clone());
+            while ticks.len() > self.capacity {
+                ticks.pop_front();
+            }
+        }
+
+        tick
+    }
+
+    /// 上次记录的行情
+    pub fn last(&self, symbol: &str) -> Option<&Quote> {
+        self.quotes.get(symbol)
+    }
+
+    /// 按时间升序
+    pub fn ticks(&self, symbol: &str) -> impl Iterator<Item = &Tick> {
+        self.ticks.get(symbol).into_iter().flatten()
+    }
+}
+
+/// 实时行情事件
+#[derive(Debug, Clone)]
+pub enum QuoteEvent {
+    /// 行情更新
+    Quotes(Vec<Quote>),
+    /// 已连接
+    Connected,
+    /// 连接断开 附原因
+    Disconnected(String),
+    /// 等待重连
+    Reconnecting {
+        /// 连续失败次数
+        attempt: u32,
+        delay: Duration,
+    },
+    /// 超时未收到数据
+    Stale(Duration),
+    /// 已降级为 HTTP 轮询
+    Polling,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_between() {
+        let prev = Quote {
+            symbol: "SH601318".to_string(),
+            now: 50.0,
+            buy: 49.99,
+            sell: 50.01,
+            turnover: 1000.0,
+            volume: 50000.0,
+            time: DateTime::parse_from_rfc3339("2022-01-28T14:59:00+08:00").ok(),
+            ..Default::default()
+        };
+        let cur = Quote { now: 50.01, turnover: 1300.0, volume: 65003.0, ..prev.clone() };
+        let tick = Tick::between(&prev, &cur).unwrap();
+        assert_eq!(tick.side, Side::Buy);
+        assert_eq!(tick.turnover, 300.0);
+        assert_eq!(tick.volume, 15003.0);
+
+        assert_eq!(
+            Tick::between(&prev, &Quote { now: 49.99, turnover: 1100.0, ..prev.clone() }).unwrap().side,
+            Side::Sell
+        );
+        assert_eq!(Tick::between(&prev, &Quote { turnover: 1100.0, ..prev.clone() }).unwrap().side, Side::Neutral);
+        assert!(Tick::between(&prev, &Quote { now: 51.0, ..prev.clone() }).is_none());
+        assert!(Tick::between(&cur, &prev).is_none());
+        assert!(Tick::between(&prev, &Quote { time: None, ..cur.clone() }).is_none());
+
+        let quote = Quote { symbol: "SH600000".to_string(), close: 7.17, now: 7.85, ..Default::default() };
+        assert_eq!(quote.limit_status(1.0), Some(LimitStatus::NearLimitUp));
+        assert_eq!(Quote { now: 7.89, ..quote.clone() }.limit_status(1.0), Some(LimitStatus::LimitUp));
+        assert_eq!(Quote { now: 7.5, ..quote }.limit_status(1.0), None);
+    }
 }
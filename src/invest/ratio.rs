@@ -0,0 +1,177 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::invest::{
+    statement::{BalanceSheet, CashFlow, IncomeStatement},
+    stock::{Dividend, Financial},
+};
+
+/// 财务比率 百分比指标以 % 为单位 报告期累计口径
+///
+/// 收益类指标按期末余额计算 非年报期间未年化
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Ratio {
+    /// 报表日期
+    pub date: NaiveDate,
+    /// 毛利率
+    pub gross_margin: f64,
+    /// 净利率
+    pub net_margin: f64,
+    /// 净资产收益率 = 净利率 × 总资产周转率 × 权益乘数
+    pub roe: f64,
+    /// 总资产收益率
+    pub roa: f64,
+    /// 总资产周转率
+    pub asset_turnover: f64,
+    /// 权益乘数
+    pub equity_multiplier: f64,
+    /// 资产负债率
+    pub debt_to_asset: f64,
+    /// 流动比率
+    pub current_ratio: f64,
+    /// 速动比率
+    pub quick_ratio: f64,
+    /// 经营现金流/净利润
+    pub cash_to_profit: f64,
+    /// 每股收益
+    pub eps: f64,
+    /// 每股净资产
+    pub ps_net_assets: f64,
+    /// 每股经营现金流
+    pub ps_cash_flow: f64,
+}
+
+/// 按利润表日期对齐计算各期比率 返回日期降序
+///
+/// 缺少对应资产负债表的期间不返回 缺少现金流量表或财务摘要时相应指标为 0
+pub fn ratios(
+    financials: &[Financial],
+    sheets: &[BalanceSheet],
+    incomes: &[IncomeStatement],
+    flows: &[CashFlow],
+) -> Vec<Ratio> {
+    let mut results = Vec::new();
+    for income in incomes.iter() {
+        let sheet = match sheets.iter().find(|s| s.date == income.date) {
+            Some(sheet) => sheet,
+            None => continue,
+        };
+        let revenue = if income.revenue != 0.0 { income.revenue } else { income.total_revenue };
+
+        let mut ratio = Ratio {
+            date: income.date,
+            gross_margin: div(revenue - income.cost, revenue) * 100.0,
+            net_margin: div(income.net_profit, revenue) * 100.0,
+            roe: div(income.net_profit, sheet.total_equity) * 100.0,
+            roa: div(income.net_profit, sheet.total_assets) * 100.0,
+            asset_turnover: div(revenue, sheet.total_assets),
+            equity_multiplier: div(sheet.total_assets, sheet.total_equity),
+            debt_to_asset: div(sheet.total_liabilities, sheet.total_assets) * 100.0,
+            current_ratio: div(sheet.current_assets, sheet.current_liabilities),
+            quick_ratio: div(sheet.current_assets - sheet.inventory, sheet.current_liabilities),
+            eps: income.basic_eps,
+            ..Default::default()
+        };
+        if let Some(flow) = flows.iter().find(|f| f.date == income.date) {
+            ratio.cash_to_profit = div(flow.operating, income.net_profit);
+        }
+        if let Some(f) = financials.iter().find(|f| f.date == income.date) {
+            if ratio.eps == 0.0 {
+                ratio.eps = f.eps;
+            }
+            ratio.ps_net_assets = f.ps_net_assets;
+            ratio.ps_cash_flow = f.ps_cash_flow;
+        }
+        results.push(ratio);
+    }
+
+    results
+}
+
+/// 市净率 每股净资产非正时返回 0
+pub fn pb(price: f64, ps_net_assets: f64) -> f64 {
+    if ps_net_assets > 0.0 {
+        price / ps_net_assets
+    } else {
+        0.0
+    }
+}
+
+/// 市盈率 亏损时返回 0
+pub fn pe(market_cap: f64, net_profit: f64) -> f64 {
+    if net_profit > 0.0 {
+        market_cap / net_profit
+    } else {
+        0.0
+    }
+}
+
+/// 股息率 % 统计除息日晚于 since 的现金分红 派息按每10股计
+pub fn dividend_yield(dividends: &[Dividend], price: f64, since: NaiveDate) -> f64 {
+    let money = dividends
+        .iter()
+        .filter(|d| d.date_dividend.is_some_and(|date| date > since))
+        .fold(0.0, |sum, d| sum + d.money / 10.0);
+    if price > 0.0 {
+        money / price * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn div(a: f64, b: f64) -> f64 {
+    if b == 0.0 {
+        0.0
+    } else {
+        a / b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::calendar::parse_date;
+
+    #[test]
+    fn test_ratios() {
+        let date = parse_date("2021-12-31").unwrap();
+        let sheets = [BalanceSheet {
+            date,
+            inventory: 20.0,
+            current_assets: 100.0,
+            total_assets: 400.0,
+            current_liabilities: 50.0,
+            total_liabilities: 200.0,
+            total_equity: 200.0,
+            ..Default::default()
+        }];
+        let incomes = [
+            IncomeStatement { date, revenue: 200.0, cost: 150.0, net_profit: 20.0, ..Default::default() },
+            IncomeStatement { date: parse_date("2021-09-30").unwrap(), revenue: 100.0, ..Default::default() },
+        ];
+        let flows = [CashFlow { date, operating: 30.0, ..Default::default() }];
+
+        let ratios = ratios(&[], &sheets, &incomes, &flows);
+        assert_eq!(ratios.len(), 1);
+        let r = &ratios[0];
+        assert_eq!(r.gross_margin, 25.0);
+        assert_eq!(r.net_margin, 10.0);
+        assert_eq!(r.roe, 10.0);
+        assert_eq!(r.roa, 5.0);
+        assert!((r.net_margin / 100.0 * r.asset_turnover * r.equity_multiplier * 100.0 - r.roe).abs() < 1e-9);
+        assert_eq!(r.debt_to_asset, 50.0);
+        assert_eq!(r.current_ratio, 2.0);
+        assert_eq!(r.quick_ratio, 1.6);
+        assert_eq!(r.cash_to_profit, 1.5);
+
+        let dividend =
+            |date: &str, money: f64| Dividend { date_dividend: parse_date(date), money, ..Default::default() };
+        let dividends = [
+            dividend("--", 1.0),
+            dividend("2021-09-03", 8.0),
+            dividend("2021-05-20", 12.0),
+            dividend("2020-09-04", 8.0),
+        ];
+        assert_eq!(dividend_yield(&dividends, 50.0, parse_date("2020-10-18").unwrap()), 4.0);
+    }
+}
@@ -0,0 +1,210 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::invest::{overview, quote::Snapshot};
+
+/// 板块分类
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SectorKind {
+    /// 新浪行业
+    Industry,
+    /// 申万一级行业
+    Sw1,
+    /// 申万二级行业
+    Sw2,
+    /// 申万三级行业
+    Sw3,
+    /// 概念板块
+    Concept,
+}
+
+impl SectorKind {
+    /// 按行情中心节点代码前缀识别 eg: sw1_110000 gn_hwqc new_blhy
+    pub fn from_node(code: &str) -> Option<Self> {
+        match code {
+            _ if code.starts_with("sw1_") => Some(SectorKind::Sw1),
+            _ if code.starts_with("sw2_") => Some(SectorKind::Sw2),
+            _ if code.starts_with("sw3_") => Some(SectorKind::Sw3),
+            _ if code.starts_with("gn_") => Some(SectorKind::Concept),
+            _ if code.starts_with("new_") => Some(SectorKind::Industry),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SectorKind::Industry => "新浪行业",
+            SectorKind::Sw1 => "申万一级",
+            SectorKind::Sw2 => "申万二级",
+            SectorKind::Sw3 => "申万三级",
+            SectorKind::Concept => "概念",
+        }
+    }
+}
+
+impl FromStr for SectorKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "industry" => Ok(SectorKind::Industry),
+            "sw1" => Ok(SectorKind::Sw1),
+            "sw2" => Ok(SectorKind::Sw2),
+            "sw3" => Ok(SectorKind::Sw3),
+            "concept" => Ok(SectorKind::Concept),
+            _ => bail!("不支持的板块分类：{}", s),
+        }
+    }
+}
+
+/// 板块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sector {
+    /// 行情中心节点代码
+    pub code: String,
+    pub name: String,
+    pub kind: SectorKind,
+}
+
+/// 个股所属板块
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Classification {
+    /// 申万一级行业 eg: 非银金融
+    pub sw1: Option<String>,
+    /// 申万二级行业 eg: 保险Ⅱ
+    pub sw2: Option<String>,
+    /// 申万三级行业
+    pub sw3: Option<String>,
+    /// 概念板块
+    pub concepts: Vec<String>,
+}
+
+/// 板块表现 由成分股快照汇总
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SectorPerformance {
+    pub code: String,
+    pub name: String,
+    /// 成分股数
+    pub total: usize,
+    pub advances: usize,
+    pub declines: usize,
+    /// 流通市值加权涨跌幅 %
+    pub change_pct: f64,
+    /// 算术平均涨跌幅 %
+    pub avg_change_pct: f64,
+    /// 成交额
+    pub volume: f64,
+    /// 总市值
+    pub market_cap: f64,
+    /// 领涨股
+    pub leader: String,
+    pub leader_change_pct: f64,
+}
+
+/// 汇总板块表现 停牌的不计入涨跌幅
+pub fn performance(sector: &Sector, members: &[Snapshot]) -> SectorPerformance {
+    let breadth = overview::breadth(members);
+    let trading = members.iter().filter(|s| s.price > 0.0 && s.turnover > 0.0).collect::<Vec<_>>();
+    let caps = trading.iter().map(|s| s.traded_market_cap).sum::<f64>();
+    let mut perf = SectorPerformance {
+        code: sector.code.to_string(),
+        name: sector.name.to_string(),
+        total: members.len(),
+        advances: breadth.advances,
+        declines: breadth.declines,
+        volume: breadth.volume,
+        market_cap: members.iter().map(|s| s.market_cap).sum(),
+        ..Default::default()
+    };
+    if !trading.is_empty() {
+        perf.avg_change_pct = trading.iter().map(|s| s.change_pct).sum::<f64>() / trading.len() as f64;
+        perf.change_pct = if caps > 0.0 {
+            trading.iter().map(|s| s.change_pct * s.traded_market_cap).sum::<f64>() / caps
+        } else {
+            perf.avg_change_pct
+        };
+    }
+    if let Some(leader) = overview::top(members, 1, |s| s.change_pct, true).first() {
+        perf.leader = leader.name.to_string();
+        perf.leader_change_pct = leader.change_pct;
+    }
+
+    perf
+}
+
+/// 由各板块成分股代码得到个股所属板块 同级多个时取首个 不属于任何板块时为 None
+pub fn classify(symbol: &str, members: &[(Sector, Vec<String>)]) -> Option<Classification> {
+    let mut class = Classification::default();
+    for (sector, _) in members.iter().filter(|(_, symbols)| symbols.iter().any(|s| s == symbol)) {
+        let name = Some(sector.name.to_string());
+        match sector.kind {
+            SectorKind::Sw1 => class.sw1 = class.sw1.or(name),
+            SectorKind::Sw2 => class.sw2 = class.sw2.or(name),
+            SectorKind::Sw3 => class.sw3 = class.sw3.or(name),
+            SectorKind::Concept => class.concepts.push(sector.name.to_string()),
+            SectorKind::Industry => {}
+        }
+    }
+
+    Some(class).filter(|c| *c != Classification::default())
+}
+
+/// 按代码或名称查找 优先完全匹配
+pub fn find<'a>(sectors: &'a [Sector], name: &str) -> Option<&'a Sector> {
+    sectors.iter().find(|s| s.code == name || s.name == name).or_else(|| sectors.iter().find(|s| s.name.contains(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_performance() {
+        let sector = Sector { code: "sw2_490200".to_string(), name: "保险Ⅱ".to_string(), kind: SectorKind::Sw2 };
+        let snapshot = |name: &str, change_pct: f64, cap: f64| Snapshot {
+            name: name.to_string(),
+            price: 10.0,
+            change: change_pct,
+            change_pct,
+            turnover: 1.0,
+            volume: 10.0,
+            traded_market_cap: cap,
+            market_cap: cap,
+            ..Default::default()
+        };
+        let members =
+            [snapshot("中国平安", 2.0, 300.0), snapshot("中国人寿", -1.0, 100.0), snapshot("新华保险", 4.0, 0.0)];
+
+        let perf = performance(&sector, &members);
+        assert_eq!((perf.total, perf.advances, perf.declines), (3, 2, 1));
+        assert_eq!(perf.change_pct, 1.25);
+        assert!((perf.avg_change_pct - 5.0 / 3.0).abs() < 1e-9);
+        assert_eq!(perf.leader, "新华保险");
+        assert_eq!(perf.market_cap, 400.0);
+
+        let sectors = [sector];
+        assert!(find(&sectors, "保险").is_some());
+        assert_eq!(SectorKind::from_node("gn_hwqc"), Some(SectorKind::Concept));
+        assert_eq!("SW2".parse::<SectorKind>().unwrap(), SectorKind::Sw2);
+
+        let sector = |code: &str, name: &str| Sector {
+            code: code.to_string(),
+            name: name.to_string(),
+            kind: SectorKind::from_node(code).unwrap(),
+        };
+        let members = [
+            (sector("sw1_490000", "非银金融"), vec!["SH601318".to_string(), "SH600030".to_string()]),
+            (sector("sw2_490200", "保险Ⅱ"), vec!["SH601318".to_string()]),
+            (sector("gn_jrkg", "金融控股"), vec!["SH601318".to_string()]),
+            (sector("gn_hgt", "沪股通"), vec!["SH601318".to_string()]),
+        ];
+        let class = classify("SH601318", &members).unwrap();
+        assert_eq!(class.sw1.as_deref(), Some("非银金融"));
+        assert_eq!(class.sw2.as_deref(), Some("保险Ⅱ"));
+        assert_eq!(class.sw3, None);
+        assert_eq!(class.concepts, vec!["金融控股", "沪股通"]);
+        assert_eq!(classify("SZ000001", &members), None);
+    }
+}
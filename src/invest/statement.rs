@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// 资产负债表 金额单位元
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BalanceSheet {
+    /// 报表日期
+    pub date: NaiveDate,
+    /// 货币资金
+    pub cash: f64,
+    /// 应收账款
+    pub accounts_receivable: f64,
+    /// 存货
+    pub inventory: f64,
+    /// 流动资产合计
+    pub current_assets: f64,
+    /// 固定资产
+    pub fixed_assets: f64,
+    /// 资产总计
+    pub total_assets: f64,
+    /// 短期借款
+    pub short_term_loans: f64,
+    /// 应付账款
+    pub accounts_payable: f64,
+    /// 流动负债合计
+    pub current_liabilities: f64,
+    /// 长期借款
+    pub long_term_loans: f64,
+    /// 负债合计
+    pub total_liabilities: f64,
+    /// 实收资本(或股本)
+    pub share_capital: f64,
+    /// 资本公积
+    pub capital_reserve: f64,
+    /// 未分配利润
+    pub undistributed_profit: f64,
+    /// 归属于母公司股东权益合计
+    pub parent_equity: f64,
+    /// 所有者权益(或股东权益)合计
+    pub total_equity: f64,
+}
+
+/// 利润表 金额单位元
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IncomeStatement {
+    /// 报表日期
+    pub date: NaiveDate,
+    /// 营业总收入
+    pub total_revenue: f64,
+    /// 营业收入
+    pub revenue: f64,
+    /// 营业总成本
+    pub total_cost: f64,
+    /// 营业成本
+    pub cost: f64,
+    /// 销售费用
+    pub selling_expenses: f64,
+    /// 管理费用
+    pub admin_expenses: f64,
+    /// 研发费用
+    pub rd_expenses: f64,
+    /// 财务费用
+    pub financial_expenses: f64,
+    /// 营业利润
+    pub operating_profit: f64,
+    /// 利润总额
+    pub total_profit: f64,
+    /// 所得税费用
+    pub income_tax: f64,
+    /// 净利润
+    pub net_profit: f64,
+    /// 归属于母公司所有者的净利润
+    pub parent_net_profit: f64,
+    /// 基本每股收益
+    pub basic_eps: f64,
+    /// 稀释每股收益
+    pub diluted_eps: f64,
+}
+
+/// 现金流量表 金额单位元
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CashFlow {
+    /// 报表日期
+    pub date: NaiveDate,
+    /// 经营活动产生的现金流量净额
+    pub operating: f64,
+    /// 投资活动产生的现金流量净额
+    pub investing: f64,
+    /// 筹资活动产生的现金流量净额
+    pub financing: f64,
+    /// 购建固定资产、无形资产和其他长期资产支付的现金
+    pub capex: f64,
+    /// 现金及现金等价物净增加额
+    pub net_increase: f64,
+    /// 期末现金及现金等价物余额
+    pub ending_cash: f64,
+}
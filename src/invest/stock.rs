@@ -1,5 +1,13 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::invest::{
+    calendar::deserialize_date,
+    currency::{Currency, Rates},
+};
+
 /// 股票信息
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Stock {
     /// 代码
     pub symbol: String,
@@ -17,16 +25,20 @@ pub struct Stock {
     pub presses: Vec<Press>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Profile {
     /// 公司名称
     pub name: String,
+    /// 英文名称
+    pub english_name: String,
     /// 简称历史
     pub used_name: String,
     /// 上市价格
     pub listing_price: f64,
     /// 上市日期
-    pub listing_date: String,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub listing_date: Option<NaiveDate>,
     /// 行业分类
     pub category: String,
     /// 主营业务
@@ -45,12 +57,111 @@ pub struct Profile {
     pub market_cap: f64,
     /// 流通市值
     pub traded_market_cap: f64,
+    /// 价格及市值的计价币种
+    pub currency: Currency,
+    /// 上市市场 eg: 上海证券交易所
+    pub market: String,
+    /// 成立日期
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub founded_date: Option<NaiveDate>,
+    /// 注册资本 元
+    pub registered_capital: f64,
+    /// 机构类型
+    pub org_type: String,
+    /// 组织形式
+    pub org_form: String,
+    /// 法人代表
+    pub legal_representative: String,
+    /// 董事长
+    pub chairman: String,
+    /// 总经理
+    pub general_manager: String,
+    /// 董事会秘书
+    pub secretary: String,
+    /// 员工人数
+    pub employees: f64,
+    pub phone: String,
+    pub fax: String,
+    pub email: String,
+    pub secretary_phone: String,
+    pub secretary_fax: String,
+    pub secretary_email: String,
+    /// 邮政编码
+    pub postcode: String,
+    /// 信息披露网址
+    pub disclosure_website: String,
+    /// 注册地址
+    pub registered_address: String,
+    /// 公司简介
+    pub introduction: String,
+    /// 首发信息
+    pub ipo: Ipo,
+    /// 高管
+    pub executives: Vec<Manager>,
+    /// 董事会
+    pub directors: Vec<Manager>,
+    /// 监事会
+    pub supervisors: Vec<Manager>,
+}
+
+impl Profile {
+    /// 价格及市值换算为 to 币种 汇率未知时返回 None
+    pub fn convert(&self, rates: &Rates, to: Currency) -> Option<Profile> {
+        let fx = |n: f64| rates.convert(n, self.currency, to);
+        Some(Profile {
+            listing_price: fx(self.listing_price)?,
+            price: fx(self.price)?,
+            market_cap: fx(self.market_cap)?,
+            traded_market_cap: fx(self.traded_market_cap)?,
+            currency: to,
+            ..self.clone()
+        })
+    }
+}
+
+/// 首次公开发行 金额单位元
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ipo {
+    /// 主承销商
+    pub underwriter: String,
+    /// 上市推荐人
+    pub sponsor: String,
+    /// 发行方式
+    pub method: String,
+    /// 发行市盈率
+    pub pe: f64,
+    /// 实际发行量 股
+    pub shares: f64,
+    /// 实际募集资金
+    pub proceeds: f64,
+    /// 发行费用
+    pub expenses: f64,
+    /// 招股公告日
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub prospectus_date: Option<NaiveDate>,
 }
 
-#[derive(Debug, Default, Clone)]
+/// 高管及董监事 任职至今时终止日期为空
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Manager {
+    pub name: String,
+    /// 职务
+    pub title: String,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+impl Manager {
+    pub fn is_current(&self, today: NaiveDate) -> bool {
+        self.end.is_none_or(|end| end >= today)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Financial {
     /// 财报日期
-    pub date: String,
+    pub date: NaiveDate,
     /// 总营收
     pub total_revenue: f64,
     /// 营收同比增长
@@ -75,20 +186,165 @@ pub struct Financial {
     pub ps_cash_flow: f64,
     /// 净资产收益率
     pub roe: f64,
+    /// 营收环比增长 仅单季及TTM口径
+    #[serde(default)]
+    pub total_revenue_qoq: f64,
+    /// 净利润环比增长 仅单季及TTM口径
+    #[serde(default)]
+    pub net_profit_qoq: f64,
+}
+
+/// 财务数据口径
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Period {
+    /// 报告期累计 即财报原始口径
+    Cumulative,
+    /// 仅年报
+    Annual,
+    /// 单季 由累计值相减得到
+    Quarterly,
+    /// 滚动四季
+    Ttm,
+}
+
+impl Period {
+    /// 得到 n 期及其同比所需的报告期累计数据条数
+    pub fn required(&self, n: usize) -> usize {
+        match self {
+            Period::Cumulative => n + 4,
+            Period::Annual => (n + 1) * 4,
+            Period::Quarterly => n + 5,
+            Period::Ttm => n + 8,
+        }
+    }
+}
+
+impl Financial {
+    /// 按报告期累计的流量指标
+    fn flows(&self) -> [f64; 5] {
+        [self.total_revenue, self.net_profit, self.net_profit_after_nrgal, self.eps, self.ps_cash_flow]
+    }
+
+    /// 推算出的单季及 TTM 数据 净资产收益率按每股收益及每股净资产重算
+    fn with_flows(&self, flows: [f64; 5]) -> Financial {
+        let mut f = self.clone();
+        f.total_revenue = flows[0];
+        f.net_profit = flows[1];
+        f.net_profit_after_nrgal = flows[2];
+        f.eps = flows[3];
+        f.ps_cash_flow = flows[4];
+        if f.ps_net_assets > 0.0 {
+            f.roe = f.eps / f.ps_net_assets * 100.0;
+        }
+        f
+    }
+}
+
+/// 将按报告期累计的财务数据转换为指定口径 返回日期降序的最近 n 期
+///
+/// 同比环比按日期匹配对应期间 缺失时为 0 无法推算的期间不返回
+pub fn financials_by_period(financials: &[Financial], period: Period, n: usize) -> Vec<Financial> {
+    let find = |date: NaiveDate| financials.iter().find(|f| f.date == date);
+    let mut results = Vec::new();
+    for f in financials.iter() {
+        let (year, month) = match report_date(f.date) {
+            Some(date) => date,
+            None => continue,
+        };
+        let converted = match period {
+            Period::Cumulative => Some(f.clone()),
+            Period::Annual if month == 12 => Some(f.clone()),
+            Period::Annual => None,
+            Period::Quarterly if month == 3 => Some(f.clone()),
+            Period::Quarterly => find(report_end(year, month - 3)).map(|prev| f.with_flows(sub(f, prev))),
+            Period::Ttm if month == 12 => Some(f.clone()),
+            Period::Ttm => match (find(report_end(year - 1, 12)), find(report_end(year - 1, month))) {
+                (Some(annual), Some(prev)) => {
+                    let mut flows = annual.flows();
+                    for (flow, (cur, prev)) in flows.iter_mut().zip(f.flows().iter().zip(prev.flows().iter())) {
+                        *flow += cur - prev;
+                    }
+                    Some(f.with_flows(flows))
+                }
+                _ => None,
+            },
+        };
+        if let Some(f) = converted {
+            results.push(f);
+        }
+    }
+
+    let snapshot = results.clone();
+    let find = |date: NaiveDate| snapshot.iter().find(|f| f.date == date);
+    for f in results.iter_mut() {
+        let (year, month) = report_date(f.date).unwrap();
+        let prev_year = find(report_end(year - 1, month));
+        f.total_revenue_rate = prev_year.map_or(0.0, |p| growth_rate(f.total_revenue, p.total_revenue));
+        f.net_profit_rate = prev_year.map_or(0.0, |p| growth_rate(f.net_profit, p.net_profit));
+        f.net_profit_after_nrgal_rate =
+            prev_year.map_or(0.0, |p| growth_rate(f.net_profit_after_nrgal, p.net_profit_after_nrgal));
+
+        let prev_quarter = match period {
+            Period::Quarterly | Period::Ttm if month == 3 => find(report_end(year - 1, 12)),
+            Period::Quarterly | Period::Ttm => find(report_end(year, month - 3)),
+            _ => None,
+        };
+        f.total_revenue_qoq = prev_quarter.map_or(0.0, |p| growth_rate(f.total_revenue, p.total_revenue));
+        f.net_profit_qoq = prev_quarter.map_or(0.0, |p| growth_rate(f.net_profit, p.net_profit));
+    }
+
+    results.truncate(n);
+    results
+}
+
+fn sub(cur: &Financial, prev: &Financial) -> [f64; 5] {
+    let mut flows = cur.flows();
+    for (flow, prev) in flows.iter_mut().zip(prev.flows().iter()) {
+        *flow -= prev;
+    }
+    flows
 }
 
-#[derive(Debug, Default)]
+/// 报告期 eg: 2021-09-30 => (2021, 9) 仅支持季末
+fn report_date(date: NaiveDate) -> Option<(i32, u32)> {
+    match (date.month(), date.day()) {
+        (3, 31) | (6, 30) | (9, 30) | (12, 31) => Some((date.year(), date.month())),
+        _ => None,
+    }
+}
+
+/// 季末日期 month 须为 3 6 9 12
+fn report_end(year: i32, month: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, if month == 3 || month == 12 { 31 } else { 30 }).unwrap_or_default()
+}
+
+/// 增长率 基数为 0 时返回 0
+pub fn growth_rate(cur: f64, prev: f64) -> f64 {
+    if prev == 0.0 {
+        0.0
+    } else {
+        (cur - prev) / prev.abs() * 100.0
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Structure {
-    pub date: String,
+    pub date: NaiveDate,
     /// 股东总数
     pub holders_num: f64,
     /// 平均持股数
     pub shares_avg: f64,
     /// 十大股东
     pub holders_ten: Vec<Holder>,
+    /// 十大流通股东
+    #[serde(default)]
+    pub holders_float: Vec<Holder>,
+    /// 基金持股
+    #[serde(default)]
+    pub funds: Vec<Fund>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Holder {
     pub name: String,
     pub shares: f64,
@@ -96,14 +352,72 @@ pub struct Holder {
     pub shares_type: String,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Fund {
+    pub name: String,
+    pub code: String,
+    pub shares: f64,
+    /// 占流通股比例
+    pub percent: f64,
+    /// 持股市值
+    pub value: f64,
+    /// 占基金净值比例
+    pub nav_percent: f64,
+}
+
+/// 股本结构 单位股
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Capital {
+    /// 变动日期
+    pub date: NaiveDate,
+    /// 变动原因
+    pub reason: String,
+    /// 总股本
+    pub total: f64,
+    /// 流通股
+    pub tradable: f64,
+    /// 流通A股
+    pub tradable_a: f64,
+    /// 限售A股
+    pub restricted_a: f64,
+    /// 流通H股
+    pub h_shares: f64,
+}
+
+/// 限售股解禁
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Unlock {
+    /// 解禁日期
+    pub date: NaiveDate,
+    /// 解禁数量
+    pub shares: f64,
+    /// 解禁股流通市值
+    pub market_value: f64,
+    /// 限售股类型 eg: 定向增发机构配售股份
+    pub kind: String,
+}
+
+impl Unlock {
+    /// 占 shares 的比例 eg: 总股本 流通股
+    pub fn percent(&self, shares: f64) -> f64 {
+        if shares > 0.0 {
+            self.shares / shares * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Dividend {
     /// 公告日
-    pub date: String,
-    /// 登记日
-    pub date_record: String,
+    pub date: NaiveDate,
+    /// 登记日 未实施时为空
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub date_record: Option<NaiveDate>,
     /// 除息日
-    pub date_dividend: String,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub date_dividend: Option<NaiveDate>,
     /// 送股
     pub shares_dividend: f64,
     /// 转增股
@@ -112,10 +426,58 @@ pub struct Dividend {
     pub money: f64,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Press {
-    pub date: String,
+    pub date: NaiveDate,
     pub title: String,
     pub url: String,
     pub file: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::calendar::parse_date;
+
+    fn financial(date: &str, total_revenue: f64) -> Financial {
+        Financial { date: parse_date(date).unwrap(), total_revenue, ..Default::default() }
+    }
+
+    #[test]
+    fn test_financials_by_period() {
+        // 缺少 2020-06-30
+        let financials = vec![
+            financial("2021-09-30", 90.0),
+            Financial { eps: 3.0, ps_net_assets: 10.0, ..financial("2021-06-30", 60.0) },
+            Financial { eps: 1.0, ps_net_assets: 10.0, roe: 9.5, ..financial("2021-03-31", 25.0) },
+            financial("2020-12-31", 100.0),
+            financial("2020-09-30", 70.0),
+            financial("2020-03-31", 20.0),
+            financial("2019-12-31", 80.0),
+        ];
+
+        let cumulative = financials_by_period(&financials, Period::Cumulative, 2);
+        assert_eq!(cumulative.len(), 2);
+        assert!((cumulative[0].total_revenue_rate - 28.571).abs() < 0.001);
+        assert_eq!(cumulative[1].total_revenue_rate, 0.0);
+
+        let quarterly = financials_by_period(&financials, Period::Quarterly, 10);
+        let dates = quarterly.iter().map(|f| f.date.to_string()).collect::<Vec<_>>();
+        assert_eq!(dates, vec!["2021-09-30", "2021-06-30", "2021-03-31", "2020-12-31", "2020-03-31"]);
+        assert_eq!(quarterly[0].total_revenue, 30.0);
+        assert_eq!(quarterly[0].total_revenue_qoq, -14.285714285714285);
+        assert_eq!(quarterly[2].total_revenue_rate, 25.0);
+        assert_eq!(quarterly[3].total_revenue, 30.0);
+        // 单季推算的重算 原始数据保留
+        assert_eq!((quarterly[1].roe, quarterly[2].roe), (20.0, 9.5));
+
+        let ttm = financials_by_period(&financials, Period::Ttm, 10);
+        assert_eq!(ttm[0].date.to_string(), "2021-09-30");
+        assert_eq!(ttm[0].total_revenue, 120.0);
+        assert_eq!(ttm.iter().find(|f| f.date.to_string() == "2020-12-31").unwrap().total_revenue_rate, 25.0);
+
+        let annual = financials_by_period(&financials, Period::Annual, 10);
+        assert_eq!(annual.len(), 2);
+        assert_eq!(annual[0].total_revenue_rate, 25.0);
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::invest::{
+    quote::Bar,
+    stock::{financials_by_period, Financial, Period},
+};
+
+/// 单日估值 亏损或净资产为负时对应指标为 0
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Valuation {
+    /// 交易日
+    pub date: NaiveDate,
+    /// 收盘价
+    pub price: f64,
+    /// 市盈率TTM
+    pub pe_ttm: f64,
+    /// 市净率
+    pub pb: f64,
+    /// 市销率TTM
+    pub ps_ttm: f64,
+}
+
+/// 估值区间统计 忽略为 0 的数据
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Band {
+    /// 统计起始日 首个有效数据的交易日
+    pub since: NaiveDate,
+    /// 数据是否覆盖整个区间 起始日晚于区间开始半月以上为否 eg: 上市不足或K线条数不够
+    pub complete: bool,
+    pub current: f64,
+    /// 当前值所处百分位
+    pub percentile: f64,
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+}
+
+/// 由日K线和报告期累计财务数据计算估值序列 按日期升序
+///
+/// 每日取截止日不晚于当日的最近一期财报 未考虑披露滞后 股本由净利润/每股收益推算
+pub fn valuations(bars: &[Bar], financials: &[Financial]) -> Vec<Valuation> {
+    let ttm = financials_by_period(financials, Period::Ttm, usize::MAX);
+    let mut results = Vec::new();
+    for bar in bars.iter() {
+        let mut valuation = Valuation { date: bar.date, price: bar.close, ..Default::default() };
+        if let Some(f) = financials.iter().find(|f| f.date <= bar.date) {
+            if f.ps_net_assets > 0.0 {
+                valuation.pb = bar.close / f.ps_net_assets;
+            }
+        }
+        if let Some(f) = ttm.iter().find(|f| f.date <= bar.date) {
+            if f.eps > 0.0 {
+                valuation.pe_ttm = bar.close / f.eps;
+                if f.total_revenue > 0.0 && f.net_profit > 0.0 {
+                    // TTM 每股营收 = 每股收益 × 营收 / 净利润
+                    valuation.ps_ttm = bar.close / (f.eps * f.total_revenue / f.net_profit);
+                }
+            }
+        }
+        results.push(valuation);
+    }
+
+    results
+}
+
+/// 最近 years 年的估值区间 以最后一个交易日为当前值 无有效数据时返回 None
+pub fn band(valuations: &[Valuation], years: i32, f: fn(&Valuation) -> f64) -> Option<Band> {
+    let last = valuations.last()?;
+    // 2 月 29 日回退到 28 日
+    let since = last
+        .date
+        .with_year(last.date.year() - years)
+        .or_else(|| last.date.pred_opt()?.with_year(last.date.year() - years))?;
+
+    let current = f(last);
+    let valid = valuations.iter().filter(|v| v.date > since && f(v) > 0.0).collect::<Vec<_>>();
+    let first = match valid.first() {
+        Some(first) if current > 0.0 => first.date,
+        _ => return None,
+    };
+    let mut values = valid.iter().map(|v| f(v)).collect::<Vec<_>>();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    Some(Band {
+        since: first,
+        complete: first - since <= Duration::days(15),
+        current,
+        percentile: values.iter().filter(|v| **v <= current).count() as f64 / values.len() as f64 * 100.0,
+        min: values[0],
+        median: values[values.len() / 2],
+        max: values[values.len() - 1],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::calendar::parse_date;
+
+    #[test]
+    fn test_valuations() {
+        let bar = |date: &str, close: f64| Bar { date: parse_date(date).unwrap(), close, ..Default::default() };
+        let bars = [bar("2021-03-01", 10.0), bar("2021-05-06", 12.0), bar("2022-01-05", 9.0)];
+        let financials = [
+            Financial {
+                date: parse_date("2021-12-31").unwrap(),
+                total_revenue: 400.0,
+                net_profit: 100.0,
+                eps: 1.0,
+                ps_net_assets: 5.0,
+                ..Default::default()
+            },
+            Financial {
+                date: parse_date("2021-03-31").unwrap(),
+                total_revenue: 100.0,
+                net_profit: 20.0,
+                eps: 0.2,
+                ps_net_assets: 4.0,
+                ..Default::default()
+            },
+            Financial {
+                date: parse_date("2020-12-31").unwrap(),
+                total_revenue: 300.0,
+                net_profit: 80.0,
+                eps: 0.8,
+                ps_net_assets: 4.0,
+                ..Default::default()
+            },
+            Financial {
+                date: parse_date("2020-03-31").unwrap(),
+                total_revenue: 60.0,
+                net_profit: 10.0,
+                eps: 0.1,
+                ..Default::default()
+            },
+        ];
+
+        let valuations = valuations(&bars, &financials);
+        assert_eq!(valuations[0].pe_ttm, 12.5);
+        assert_eq!(valuations[0].pb, 2.5);
+        // TTM: eps 0.9 营收 340
+        assert!((valuations[1].pe_ttm - 12.0 / 0.9).abs() < 1e-9);
+        assert!((valuations[1].ps_ttm - 12.0 / (0.9 * 340.0 / 90.0)).abs() < 1e-9);
+        assert_eq!(valuations[2].pe_ttm, 9.0);
+        assert_eq!(valuations[2].ps_ttm, 2.25);
+
+        let band = band(&valuations, 3, |v| v.pe_ttm).unwrap();
+        assert_eq!(band.current, 9.0);
+        assert!((band.percentile - 100.0 / 3.0).abs() < 1e-9);
+        assert!((band.max - 12.0 / 0.9).abs() < 1e-9);
+        assert!(!band.complete);
+    }
+}
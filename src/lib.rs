@@ -1,3 +1,7 @@
 pub mod cli;
 pub mod invest;
+pub mod render;
+pub mod screener;
 pub mod sina;
+pub mod store;
+pub mod table;
@@ -1,23 +1,48 @@
 use std::{
     collections::HashMap,
+    fs::{self, File, OpenOptions},
     io::{stdout, Write},
+    path::PathBuf,
     str::FromStr,
+    time::Duration,
 };
 
 use anyhow::{bail, Result};
+use chrono::{Datelike, Local, NaiveDate, Utc};
 use crossterm::{cursor, style::Stylize, terminal, terminal::ClearType, ExecutableCommand};
+use futures_util::StreamExt;
 use once_cell::sync::Lazy;
 use owo_colors::OwoColorize;
 use rains::{
-    cli::{Opts, Subcommand},
-    invest::{quote::Quote, Exchange, Investment, Market},
-    sina::Sina,
+    cli::{Format, Opts, PressAction, SectorAction, StatementKind, Subcommand},
+    invest::{
+        calendar::{self, Phase},
+        currency::{Currency, Rates},
+        dividend,
+        holder::{self, Change},
+        overview, press,
+        quote::{LimitStatus, Quote, QuoteEvent, Side, TickLog},
+        ratio::{self, Ratio},
+        sector,
+        statement::{BalanceSheet, CashFlow, IncomeStatement},
+        stock::{financials_by_period, Dividend, Financial, Period, Profile, Structure},
+        valuation::{self, Band, Valuation},
+        Exchange, Investment, Market,
+    },
+    render::{self, fmt_num, fmt_rate, fmt_time, Row},
+    screener::{self, Filter},
+    sina::{QuoteStream, Sina, WsOptions},
+    store::{self, Store},
 };
 use regex::Regex;
-use tokio::sync::Mutex;
-use tracing::{debug, error};
+use serde::Serialize;
+use tokio::{select, signal, sync::Mutex};
+use tracing::{debug, error, warn};
 use tracing_subscriber::EnvFilter;
 
+/// 默认距涨跌停标记阈值 %
+const NEAR_LIMIT: f64 = 1.0;
+
 static SINA: Lazy<Mutex<Sina>> = Lazy::new(|| Mutex::new(Sina::default()));
 
 #[tokio::main]
@@ -30,7 +55,6 @@ async fn main() {
 
 // todo use tui table & chart
 
-#[allow(clippy::mutex_atomic)]
 async fn run() -> Result<()> {
     let args = Opts::parse_args()?;
     if args.debug {
@@ -41,19 +65,58 @@ async fn run() -> Result<()> {
     }
     debug!("args: {:?}", args);
 
+    let offline = args.offline;
+    let currency = args.currency;
+    match args.cmd {
+        Subcommand::Info { .. } | Subcommand::Quote { .. } | Subcommand::Replay { .. } | Subcommand::Compare { .. } => {
+        }
+        _ if currency.is_some() => bail!("--currency 仅支持 info、quote、replay、compare"),
+        _ => {}
+    }
     match args.cmd {
-        Subcommand::Search { query, limit } => match Sina::default().search(&query).await {
+        Subcommand::Search { query, limit } => match if offline {
+            Store::open_default().and_then(|store| store.search(&query))
+        } else {
+            Sina::default().search(&query).await
+        } {
             Ok(results) => {
                 let limit = if (limit as usize) < results.len() { limit as usize } else { results.len() };
                 for i in 0..limit {
                     let invest = results.get(i).unwrap();
-                    println!("{:<8}\t{}", invest.symbol, invest.name);
+                    match &invest.sector {
+                        Some(sector) => {
+                            let industry = [&sector.sw1, &sector.sw2, &sector.sw3].into_iter().flatten();
+                            println!(
+                                "{:<8}\t{}\t{}\t{}",
+                                invest.symbol,
+                                invest.name,
+                                industry.map(|s| s.as_str()).collect::<Vec<_>>().join("/"),
+                                sector.concepts.join(",")
+                            )
+                        }
+                        None => println!("{:<8}\t{}", invest.symbol, invest.name),
+                    }
                 }
             }
             Err(err) => error!("{}", err),
         },
-        Subcommand::Info { symbol, all, financials, structure, dividends, presses } => {
-            match check_symbol(&symbol).await {
+        Subcommand::Info {
+            symbol,
+            all,
+            financials,
+            structure,
+            holders_diff,
+            capital,
+            managers,
+            dividends,
+            presses,
+            statements,
+            periods,
+            annual,
+            quarterly,
+            ttm,
+        } => {
+            match resolve_symbol(&symbol, offline).await {
                 Ok(invest) => {
                     match invest.exchange {
                         Some(Exchange::Sse) | Some(Exchange::SZse) | Some(Exchange::Bse) => {}
@@ -61,54 +124,92 @@ async fn run() -> Result<()> {
                     }
 
                     let symbol = invest.symbol.clone();
-                    match SINA.lock().await.profile(&symbol).await {
-                    Ok(profile) => println!(
-                        "{}\n证券代码\t{}\n简称历史\t{}\n公司名称\t{}\n上市日期\t{}\n发行价格\t{:.2}\n行业分类\t{}\n主营业务\t{}\n办公地址\t{}\n公司网址\t{}\n当前价格\t{:.2}\n市净率PB\t{:.2}\n市盈率TTM\t{:.2}\n总市值  \t{}\n流通市值\t{}",
-                        "基本信息".bold(),
-                        &symbol,
-                        profile.used_name,
-                        profile.name,
-                        profile.listing_date,
-                        profile.listing_price,
-                        profile.category,
-                        profile.business,
-                        profile.business_address,
-                        profile.website.underline(),
-                        profile.price,
-                        profile.pb,
-                        profile.pe_ttm,
-                        fmt_num(&profile.market_cap),
-                        fmt_num(&profile.traded_market_cap)
-                    ),
-                    Err(err) => error!("{}", err),
-                }
+                    let fx = fx_rates(currency, offline).await?;
+                    let mut price = 0.0;
+                    match if offline {
+                        Store::open_default().and_then(|store| store.profile(&symbol))
+                    } else {
+                        SINA.lock().await.profile(&symbol).await
+                    } {
+                        Ok(profile) => {
+                            // 股息率按原币种价格计算
+                            price = profile.price;
+                            let profile = convert_profile(profile, fx.as_ref());
+                            println!(
+                                "{}\n证券代码\t{}\n简称历史\t{}\n公司名称\t{}\n上市日期\t{}\n发行价格\t{:.2}\n行业分类\t{}\n主营业务\t{}\n办公地址\t{}\n公司网址\t{}\n当前价格\t{:.2} {}\n市净率PB\t{:.2}\n市盈率TTM\t{:.2}\n总市值  \t{}\n流通市值\t{}",
+                                "基本信息".bold(),
+                                &symbol,
+                                profile.used_name,
+                                profile.name,
+                                profile.listing_date.map_or(String::new(), |d| d.to_string()),
+                                profile.listing_price,
+                                profile.category,
+                                profile.business,
+                                profile.business_address,
+                                profile.website.underline(),
+                                profile.price,
+                                profile.currency.as_str(),
+                                profile.pb,
+                                profile.pe_ttm,
+                                fmt_num(&profile.market_cap),
+                                fmt_num(&profile.traded_market_cap)
+                            );
+                            print_profile_details(&profile, all);
+                            if all || managers {
+                                print_managers(&profile);
+                            }
+                        }
+                        Err(err) => error!("{}", err),
+                    }
 
-                    if all || financials {
-                        println!("\n{}", "财务指标".bold());
+                    if all || financials || annual || quarterly || ttm {
+                        let period = match (annual, quarterly, ttm) {
+                            (true, _, _) => Period::Annual,
+                            (_, true, _) => Period::Quarterly,
+                            (_, _, true) => Period::Ttm,
+                            _ => Period::Cumulative,
+                        };
+                        println!(
+                            "\n{}",
+                            match period {
+                                Period::Cumulative => "财务指标",
+                                Period::Annual => "财务指标(年报)",
+                                Period::Quarterly => "财务指标(单季)",
+                                Period::Ttm => "财务指标(TTM)",
+                            }
+                            .bold()
+                        );
                         let symbol = symbol.clone();
                         tokio::spawn(async move {
-                            match SINA.lock().await.financials(&symbol[2..]).await {
+                            match if offline {
+                                Store::open_default().and_then(|store| store.financials(&symbol))
+                            } else {
+                                SINA.lock().await.financials(&symbol[2..], period.required(periods)).await
+                            } {
                                 Ok(financials) => {
-                                    // align todo change
-                                    let cols = vec!["截止日期", "总营收", "净利润", "每股净资产", "每股资本公积金"];
-                                    for (i, col) in cols.iter().enumerate() {
-                                        let mut output = format!("{:<16}", *col);
-                                        for f in financials.iter() {
-                                            match i {
-                                                0 => output.push_str(&format!("\t{:<16}", f.date)),
-                                                1 => output.push_str(&format!("\t{:<16}", fmt_num(&f.total_revenue))),
-                                                2 => output.push_str(&format!("\t{:<16}", fmt_num(&f.net_profit))),
-                                                3 => output
-                                                    .push_str(&format!("\t{:<16}", format!("{:.4}", f.ps_net_assets))),
-                                                4 => output.push_str(&format!(
-                                                    "\t{:<16}",
-                                                    format!("{:.4}", f.ps_capital_reserve)
-                                                )),
-                                                _ => {}
-                                            }
-                                        }
-                                        println!("{}", output);
+                                    let financials = financials_by_period(&financials, period, periods);
+                                    let col = |f: fn(&Financial) -> String| financials.iter().map(f).collect();
+                                    let mut rows = vec![
+                                        ("截止日期", col(|f| f.date.to_string())),
+                                        ("总营收", col(|f| fmt_num(&f.total_revenue))),
+                                        ("营收同比", col(|f| fmt_rate(f.total_revenue_rate))),
+                                        ("营收环比", col(|f| fmt_rate(f.total_revenue_qoq))),
+                                        ("净利润", col(|f| fmt_num(&f.net_profit))),
+                                        ("净利润同比", col(|f| fmt_rate(f.net_profit_rate))),
+                                        ("净利润环比", col(|f| fmt_rate(f.net_profit_qoq))),
+                                        ("扣非净利润", col(|f| fmt_num(&f.net_profit_after_nrgal))),
+                                        ("扣非净利润同比", col(|f| fmt_rate(f.net_profit_after_nrgal_rate))),
+                                        ("每股收益", col(|f| format!("{:.4}", f.eps))),
+                                        ("每股净资产", col(|f| format!("{:.4}", f.ps_net_assets))),
+                                        ("每股资本公积金", col(|f| format!("{:.4}", f.ps_capital_reserve))),
+                                        ("每股未分配利润", col(|f| format!("{:.4}", f.eps_undistributed))),
+                                        ("每股经营现金流", col(|f| format!("{:.4}", f.ps_cash_flow))),
+                                        ("净资产收益率", col(|f| fmt_rate(f.roe))),
+                                    ];
+                                    if !matches!(period, Period::Quarterly | Period::Ttm) {
+                                        rows.retain(|(name, _)| !name.ends_with("环比"));
                                     }
+                                    write_rows(&rows);
                                 }
                                 Err(err) => error!("{}", err),
                             }
@@ -117,29 +218,204 @@ async fn run() -> Result<()> {
                         .unwrap();
                     }
 
-                    if all || structure {
+                    if let Some(kind) = statements {
+                        let code = symbol[2..].to_string();
+                        let symbol = symbol.clone();
+                        let store = if offline { Some(Store::open_default()?) } else { None };
+                        match kind {
+                            StatementKind::Balance => {
+                                println!("\n{}", "资产负债表".bold());
+                                match match &store {
+                                    Some(store) => store.balance_sheets(&symbol).map(|mut s| {
+                                        s.truncate(periods);
+                                        s
+                                    }),
+                                    None => SINA.lock().await.balance_sheets(&code, periods).await,
+                                } {
+                                    Ok(sheets) => {
+                                        let col = |f: fn(&BalanceSheet) -> f64| {
+                                            sheets.iter().map(|s| fmt_num(&f(s))).collect()
+                                        };
+                                        write_rows(&[
+                                            ("报表日期", sheets.iter().map(|s| s.date.to_string()).collect()),
+                                            ("货币资金", col(|s| s.cash)),
+                                            ("应收账款", col(|s| s.accounts_receivable)),
+                                            ("存货", col(|s| s.inventory)),
+                                            ("流动资产合计", col(|s| s.current_assets)),
+                                            ("固定资产", col(|s| s.fixed_assets)),
+                                            ("资产总计", col(|s| s.total_assets)),
+                                            ("短期借款", col(|s| s.short_term_loans)),
+                                            ("应付账款", col(|s| s.accounts_payable)),
+                                            ("流动负债合计", col(|s| s.current_liabilities)),
+                                            ("长期借款", col(|s| s.long_term_loans)),
+                                            ("负债合计", col(|s| s.total_liabilities)),
+                                            ("股本", col(|s| s.share_capital)),
+                                            ("资本公积", col(|s| s.capital_reserve)),
+                                            ("未分配利润", col(|s| s.undistributed_profit)),
+                                            ("归母股东权益", col(|s| s.parent_equity)),
+                                            ("股东权益合计", col(|s| s.total_equity)),
+                                        ]);
+                                    }
+                                    Err(err) => error!("{}", err),
+                                }
+                            }
+                            StatementKind::Income => {
+                                println!("\n{}", "利润表".bold());
+                                match match &store {
+                                    Some(store) => store.income_statements(&symbol).map(|mut s| {
+                                        s.truncate(periods);
+                                        s
+                                    }),
+                                    None => SINA.lock().await.income_statements(&code, periods).await,
+                                } {
+                                    Ok(incomes) => {
+                                        let col = |f: fn(&IncomeStatement) -> f64| {
+                                            incomes.iter().map(|s| fmt_num(&f(s))).collect()
+                                        };
+                                        write_rows(&[
+                                            ("报表日期", incomes.iter().map(|s| s.date.to_string()).collect()),
+                                            ("营业总收入", col(|s| s.total_revenue)),
+                                            ("营业收入", col(|s| s.revenue)),
+                                            ("营业总成本", col(|s| s.total_cost)),
+                                            ("营业成本", col(|s| s.cost)),
+                                            ("销售费用", col(|s| s.selling_expenses)),
+                                            ("管理费用", col(|s| s.admin_expenses)),
+                                            ("研发费用", col(|s| s.rd_expenses)),
+                                            ("财务费用", col(|s| s.financial_expenses)),
+                                            ("营业利润", col(|s| s.operating_profit)),
+                                            ("利润总额", col(|s| s.total_profit)),
+                                            ("所得税费用", col(|s| s.income_tax)),
+                                            ("净利润", col(|s| s.net_profit)),
+                                            ("归母净利润", col(|s| s.parent_net_profit)),
+                                            (
+                                                "基本每股收益",
+                                                incomes.iter().map(|s| format!("{:.4}", s.basic_eps)).collect(),
+                                            ),
+                                            (
+                                                "稀释每股收益",
+                                                incomes.iter().map(|s| format!("{:.4}", s.diluted_eps)).collect(),
+                                            ),
+                                        ]);
+                                    }
+                                    Err(err) => error!("{}", err),
+                                }
+                            }
+                            StatementKind::Cashflow => {
+                                println!("\n{}", "现金流量表".bold());
+                                match match &store {
+                                    Some(store) => store.cash_flows(&symbol).map(|mut s| {
+                                        s.truncate(periods);
+                                        s
+                                    }),
+                                    None => SINA.lock().await.cash_flows(&code, periods).await,
+                                } {
+                                    Ok(flows) => {
+                                        let col =
+                                            |f: fn(&CashFlow) -> f64| flows.iter().map(|s| fmt_num(&f(s))).collect();
+                                        write_rows(&[
+                                            ("报表日期", flows.iter().map(|s| s.date.to_string()).collect()),
+                                            ("经营活动现金流净额", col(|s| s.operating)),
+                                            ("投资活动现金流净额", col(|s| s.investing)),
+                                            ("筹资活动现金流净额", col(|s| s.financing)),
+                                            ("资本开支", col(|s| s.capex)),
+                                            ("现金净增加额", col(|s| s.net_increase)),
+                                            ("期末现金余额", col(|s| s.ending_cash)),
+                                        ]);
+                                    }
+                                    Err(err) => error!("{}", err),
+                                }
+                            }
+                        }
+                    }
+
+                    if all || structure || holders_diff {
                         println!("\n{}", "股东结构".bold());
                         let symbol = invest.symbol.clone();
                         tokio::spawn(async move {
-                            match SINA.lock().await.structures(&symbol[2..]).await {
+                            match if offline {
+                                Store::open_default().and_then(|store| store.structures(&symbol))
+                            } else {
+                                SINA.lock().await.structures(&symbol[2..]).await
+                            } {
                                 Ok(structures) => {
                                     if structures.is_empty() {
                                         return;
                                     }
 
-                                    let first = structures.get(0).unwrap();
-                                    let mut holders = String::new();
-                                    let mut shares = String::new();
-                                    for s in structures.iter() {
-                                        holders.push_str(&format!("{}({})\t", fmt_num(&s.holders_num), s.date));
-                                        shares.push_str(&format!("{}({})\t", fmt_num(&s.shares_avg), s.date));
+                                    if all || structure {
+                                        print_structure(&structures);
+                                    }
+                                    if all || holders_diff {
+                                        print_holders_diff(&structures);
+                                    }
+                                }
+                                Err(err) => error!("{}", err),
+                            }
+                        })
+                        .await
+                        .unwrap()
+                    }
+
+                    if all || capital {
+                        println!("\n{}", "股本结构".bold());
+                        let symbol = invest.symbol.clone();
+                        tokio::spawn(async move {
+                            let (capitals, unlocks) = if offline {
+                                match Store::open_default() {
+                                    Ok(store) => (store.capitals(&symbol), store.unlocks(&symbol)),
+                                    Err(err) => {
+                                        error!("{}", err);
+                                        return;
+                                    }
+                                }
+                            } else {
+                                let sina = SINA.lock().await;
+                                (sina.capitals(&symbol[2..]).await, sina.unlocks(&symbol).await)
+                            };
+                            let capitals = match capitals {
+                                Ok(capitals) => capitals,
+                                Err(err) => {
+                                    error!("{}", err);
+                                    return;
+                                }
+                            };
+                            println!("变动日期 \t 总股本 \t 流通A股 \t 限售A股 \t 流通H股 \t 变动原因");
+                            for c in capitals.iter() {
+                                println!(
+                                    "{} \t {} \t {} \t {} \t {} \t {}",
+                                    c.date,
+                                    fmt_num(&c.total),
+                                    fmt_num(&c.tradable_a),
+                                    fmt_num(&c.restricted_a),
+                                    fmt_num(&c.h_shares),
+                                    c.reason
+                                );
+                            }
+
+                            println!("\n{}", "限售解禁".bold());
+                            match unlocks {
+                                Ok(unlocks) => {
+                                    let today = Local::now().date_naive();
+                                    let mut upcoming = unlocks.iter().filter(|u| u.date >= today).collect::<Vec<_>>();
+                                    if upcoming.is_empty() {
+                                        println!("暂无待解禁");
+                                        return;
                                     }
-                                    println!(
-                                        "截止日期\t{}\n股东户数\t{}\n平均持股\t{}\n十大股东",
-                                        first.date, holders, shares
-                                    );
-                                    for (i, h) in first.holders_ten.iter().enumerate() {
-                                        println!("{}\t{}({}% {})", i + 1, h.name, h.percent, fmt_num(&h.shares))
+                                    upcoming.sort_by_key(|u| u.date);
+                                    // 比例按最新股本计算
+                                    let (total, tradable) =
+                                        capitals.first().map_or((0.0, 0.0), |c| (c.total, c.tradable_a));
+                                    println!("解禁日期 \t 解禁数量 \t 占总股本 \t 占流通A股 \t 解禁市值 \t 类型");
+                                    for u in upcoming {
+                                        println!(
+                                            "{} \t {} \t {:<8} \t {:<8} \t {} \t {}",
+                                            u.date,
+                                            fmt_num(&u.shares),
+                                            fmt_rate(u.percent(total)),
+                                            fmt_rate(u.percent(tradable)),
+                                            fmt_num(&u.market_value),
+                                            u.kind
+                                        );
                                     }
                                 }
                                 Err(err) => error!("{}", err),
@@ -153,9 +429,43 @@ async fn run() -> Result<()> {
                         println!("\n{}", "分红送配".bold());
                         let symbol = invest.symbol.clone();
                         tokio::spawn(async move {
-                            match SINA.lock().await.dividends(&symbol[2..]).await {
+                            let (dividends, financials, capitals) = if offline {
+                                match Store::open_default() {
+                                    Ok(store) => {
+                                        (store.dividends(&symbol), store.financials(&symbol), store.capitals(&symbol))
+                                    }
+                                    Err(err) => {
+                                        error!("{}", err);
+                                        return;
+                                    }
+                                }
+                            } else {
+                                let sina = SINA.lock().await;
+                                (
+                                    sina.dividends(&symbol[2..]).await,
+                                    sina.financials(&symbol[2..], 40).await,
+                                    sina.capitals(&symbol[2..]).await,
+                                )
+                            };
+                            match dividends {
                                 Ok(dividends) => {
-                                    println!("公告日期 \t 分红送配 \t\t\t 除权除息日 \t 股权登记日");
+                                    // 无财务或股本数据时不计算股利支付率
+                                    let financials = financials.unwrap_or_else(|err| {
+                                        debug!("{}", err);
+                                        vec![]
+                                    });
+                                    let capitals = capitals.unwrap_or_else(|err| {
+                                        debug!("{}", err);
+                                        vec![]
+                                    });
+                                    print_dividend_summary(&dividend::summary(
+                                        &dividends,
+                                        &financials,
+                                        &capitals,
+                                        price,
+                                        Local::now().date_naive(),
+                                    ));
+                                    println!("\n公告日期 \t 分红送配 \t\t\t 除权除息日 \t 股权登记日");
                                     for d in dividends.iter() {
                                         let mut info = String::from("10");
                                         if d.shares_dividend > 0.0 {
@@ -174,8 +484,8 @@ async fn run() -> Result<()> {
                                             "{} \t {} \t\t {} \t {}",
                                             d.date,
                                             if info.len() < 19 { format!("{}\t", info) } else { info },
-                                            if d.date_dividend.len() < 3 { " -\t" } else { &d.date_dividend },
-                                            if d.date_record.len() < 3 { " - " } else { &d.date_record }
+                                            d.date_dividend.map_or(" -\t".to_string(), |d| d.to_string()),
+                                            d.date_record.map_or(" - ".to_string(), |d| d.to_string())
                                         );
                                     }
                                 }
@@ -190,7 +500,11 @@ async fn run() -> Result<()> {
                         println!("\n{}", "最新公告".bold());
                         let symbol = invest.symbol.clone();
                         tokio::spawn(async move {
-                            match SINA.lock().await.presses(&symbol[2..]).await {
+                            match if offline {
+                                Store::open_default().and_then(|store| store.presses(&symbol))
+                            } else {
+                                SINA.lock().await.presses(&symbol[2..]).await
+                            } {
                                 Ok(presses) => {
                                     for p in presses.iter() {
                                         println!("{}\t{}\t{}", p.date, p.title, p.url);
@@ -206,74 +520,524 @@ async fn run() -> Result<()> {
                 Err(err) => error!("{}", err),
             }
         }
-        Subcommand::Quote { symbol, no_check, realtime, multiline } => {
-            let parts = symbol.split(',').collect::<Vec<&str>>();
+        Subcommand::Quote {
+            symbol,
+            no_check,
+            realtime,
+            multiline,
+            poll,
+            ticks,
+            tick_file,
+            record,
+            near,
+            limits,
+            no_wait,
+        } => {
             let mut symbols = Vec::new();
-            for symbol in parts {
+            for symbol in split_symbols(&symbol)? {
                 if no_check {
-                    symbols.push(symbol.to_string());
+                    symbols.push(symbol);
                 } else {
-                    match check_symbol(symbol).await {
+                    match resolve_symbol(&symbol, offline).await {
                         Ok(invest) => symbols.push(invest.symbol),
                         Err(err) => error!("{} {}", symbol, err),
                     }
                 }
             }
 
+            if offline && realtime {
+                bail!("离线模式不支持实时行情");
+            }
+            let fx = fx_rates(currency, offline).await?;
+            if offline {
+                let store = Store::open_default()?;
+                for symbol in symbols.iter() {
+                    match store.quote(symbol) {
+                        Ok(quote) if !limits || quote.limit_status(near).is_some() => {
+                            println!("{}", fmt_quote(&quote, None, near, fx.as_ref()))
+                        }
+                        Ok(_) => {}
+                        Err(err) => error!("{}", err),
+                    }
+                }
+                return Ok(());
+            }
+
+            let exchanges = symbols.iter().filter_map(|s| Exchange::from_str(&s[..2]).ok()).collect::<Vec<_>>();
             let symbols = symbols.join(",");
             if realtime {
-                // 首次输出
-                let written = std::sync::Mutex::new(false);
-                // 实际数量
-                let len = std::sync::Mutex::new(0);
-                // 位置对应表
-                let lines = std::sync::Mutex::new(HashMap::new());
-                // 当前位置
-                let cur = std::sync::Mutex::new(0);
-                Sina::quotes_ws(&symbols, |quotes: Vec<Quote>| {
-                    let mut l = len.lock().unwrap();
-                    if multiline && quotes.len() == 1 && *l == 0 {
-                        write_quote(quotes.get(0).unwrap());
-                        return;
+                if !calendar::is_covered(Local::now().date_naive()) {
+                    warn!("{}，休市时段可能显示为交易中", uncovered_note());
+                }
+                if !no_wait && !wait_open(&exchanges).await {
+                    return Ok(());
+                }
+
+                let tick_file = match tick_file {
+                    Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+                    None => None,
+                };
+                let board = Board {
+                    multiline,
+                    show_ticks: ticks,
+                    tick_file,
+                    ticks: TickLog::new(ticks),
+                    near,
+                    fx,
+                    ..Default::default()
+                };
+                let mut opts = WsOptions { record: record.map(PathBuf::from), ..Default::default() };
+                let stream = match poll {
+                    Some(secs) => {
+                        opts.poll_interval = Duration::from_secs(secs.max(1));
+                        SINA.lock().await.quote_poll_stream(&symbols, &opts)?
                     }
+                    None => SINA.lock().await.quote_stream_with(&symbols, &opts)?,
+                };
+                watch(board, stream).await;
+            } else {
+                match SINA.lock().await.quotes(&symbols).await {
+                    Ok(quotes) => {
+                        for quote in quotes.iter().filter(|q| !limits || q.limit_status(near).is_some()) {
+                            println!("{}", fmt_quote(quote, None, near, fx.as_ref()));
+                        }
+                    }
+                    Err(err) => error!("{}", err),
+                }
+            }
+        }
+        Subcommand::Replay { file, speed, multiline, ticks } => {
+            let fx = fx_rates(currency, offline).await?;
+            let stream = SINA.lock().await.replay(&file, speed)?;
+            let board = Board {
+                multiline,
+                show_ticks: ticks,
+                ticks: TickLog::new(ticks),
+                near: NEAR_LIMIT,
+                fx,
+                ..Default::default()
+            };
+            watch(board, stream).await;
+        }
+        Subcommand::Ratios { symbol, periods, annual } => {
+            let invest = resolve_symbol(&symbol, offline).await?;
+            match invest.exchange {
+                Some(Exchange::Sse) | Some(Exchange::SZse) | Some(Exchange::Bse) => {}
+                _ => bail!("当前仅支持沪深北证股票财务比率"),
+            }
+
+            let symbol = invest.symbol.clone();
+            let (financials, sheets, incomes, flows) = if offline {
+                let store = Store::open_default()?;
+                (
+                    store.financials(&symbol)?,
+                    store.balance_sheets(&symbol)?,
+                    store.income_statements(&symbol)?,
+                    store.cash_flows(&symbol)?,
+                )
+            } else {
+                let sina = SINA.lock().await;
+                let code = &symbol[2..];
+                let limit = if annual { Period::Annual } else { Period::Cumulative }.required(periods);
+                (
+                    sina.financials(code, limit).await?,
+                    sina.balance_sheets(code, limit).await?,
+                    sina.income_statements(code, limit).await?,
+                    sina.cash_flows(code, limit).await?,
+                )
+            };
+
+            let mut ratios = ratio::ratios(&financials, &sheets, &incomes, &flows);
+            if annual {
+                ratios.retain(|r| r.date.month() == 12);
+            }
+            ratios.truncate(periods);
+            if ratios.is_empty() {
+                bail!("{} 无可用财务报表数据", symbol);
+            }
+
+            println!("{} {}", symbol, invest.name.bold());
+            let col = |f: fn(&Ratio) -> String| ratios.iter().map(f).collect();
+            write_rows(&[
+                ("报表日期", col(|r| r.date.to_string())),
+                ("毛利率", col(|r| fmt_rate(r.gross_margin))),
+                ("净利率", col(|r| fmt_rate(r.net_margin))),
+                ("净资产收益率", col(|r| fmt_rate(r.roe))),
+                ("  总资产周转率", col(|r| format!("{:.4}", r.asset_turnover))),
+                ("  权益乘数", col(|r| format!("{:.2}", r.equity_multiplier))),
+                ("总资产收益率", col(|r| fmt_rate(r.roa))),
+                ("资产负债率", col(|r| fmt_rate(r.debt_to_asset))),
+                ("流动比率", col(|r| format!("{:.2}", r.current_ratio))),
+                ("速动比率", col(|r| format!("{:.2}", r.quick_ratio))),
+                ("现金流/净利润", col(|r| format!("{:.2}", r.cash_to_profit))),
+                ("每股收益", col(|r| format!("{:.4}", r.eps))),
+                ("每股净资产", col(|r| format!("{:.4}", r.ps_net_assets))),
+                ("每股经营现金流", col(|r| format!("{:.4}", r.ps_cash_flow))),
+            ]);
+        }
+        Subcommand::Valuation { symbol, monthly } => {
+            let invest = resolve_symbol(&symbol, offline).await?;
+            match invest.exchange {
+                Some(Exchange::Sse) | Some(Exchange::SZse) | Some(Exchange::Bse) => {}
+                _ => bail!("当前仅支持沪深北证股票历史估值"),
+            }
 
-                    let mut stdout = stdout();
-                    let mut w = written.lock().unwrap();
-                    let mut m = lines.lock().unwrap();
-                    let mut c = cur.lock().unwrap();
-                    if !*w {
-                        *l = quotes.len();
-                        *c = quotes.len() - 1;
+            let symbol = invest.symbol.clone();
+            let (bars, financials) = if offline {
+                let store = Store::open_default()?;
+                (store.bars(&symbol)?, store.financials(&symbol)?)
+            } else {
+                let sina = SINA.lock().await;
+                // K线最多 1023 条约 4 年 更长区间以本地同步数据为准 不足时标注
+                (sina.bars(&symbol, 1023).await?, sina.financials(&symbol[2..], 44).await?)
+            };
+            let history = valuation::valuations(&bars, &financials);
+            let last = match history.last() {
+                Some(last) => last,
+                None => bail!("{} 无历史行情数据", symbol),
+            };
+
+            println!("{} {} {} 收盘 {:.2}", symbol, invest.name.bold(), last.date, last.price);
+            let names = ["市盈率TTM", "市净率", "市销率TTM"];
+            let metrics: [fn(&Valuation) -> f64; 3] = [|v| v.pe_ttm, |v| v.pb, |v| v.ps_ttm];
+            for years in [3, 5, 10] {
+                let bands = metrics.iter().map(|f| valuation::band(&history, years, *f)).collect::<Vec<_>>();
+                let since =
+                    bands.iter().flatten().map(|b| b.since).min().map_or_else(|| "-".to_string(), |d| d.to_string());
+                let title = match bands.iter().flatten().any(|b| !b.complete) {
+                    true => format!("近{}年 自{} 数据不足{}年", years, since, years),
+                    false => format!("近{}年 自{}", years, since),
+                };
+                println!("\n{}", title.bold());
+                let col = |f: fn(&Band) -> String| {
+                    bands.iter().map(|b| b.as_ref().map_or(" - ".to_string(), f)).collect::<Vec<_>>()
+                };
+                write_rows(&[
+                    ("", names.iter().map(|name| name.to_string()).collect()),
+                    ("起始", col(|b| b.since.to_string())),
+                    ("当前", col(|b| format!("{:.2}", b.current))),
+                    ("分位", col(|b| fmt_rate(b.percentile))),
+                    ("最低", col(|b| format!("{:.2}", b.min))),
+                    ("中位", col(|b| format!("{:.2}", b.median))),
+                    ("最高", col(|b| format!("{:.2}", b.max))),
+                ]);
+            }
+
+            if monthly {
+                println!("\n{}", "月末估值".bold());
+                write_rows(&[(
+                    "日期",
+                    vec!["收盘".to_string(), "市盈率TTM".to_string(), "市净率".to_string(), "市销率TTM".to_string()],
+                )]);
+                for (i, v) in history.iter().enumerate() {
+                    let month_end = history.get(i + 1).is_none_or(|next| next.date.month() != v.date.month());
+                    if month_end {
+                        let fmt = |n: f64| if n > 0.0 { format!("{:.2}", n) } else { " - ".to_string() };
+                        write_rows(&[(
+                            &v.date.to_string(),
+                            vec![fmt(v.price), fmt(v.pe_ttm), fmt(v.pb), fmt(v.ps_ttm)],
+                        )]);
                     }
-                    for (i, quote) in quotes.iter().enumerate() {
-                        let k = quote.symbol.to_string();
-                        if *w {
-                            if let Some(line) = m.get(&k) {
-                                if *c >= *line {
-                                    stdout.execute(cursor::MoveToPreviousLine((*c - *line + 1) as u16)).unwrap();
-                                } else if *line > *c + 1 {
-                                    stdout.execute(cursor::MoveToNextLine((*line - *c - 1) as u16)).unwrap();
-                                }
-                                *c = *line;
+                }
+            }
+        }
+        Subcommand::Compare { symbol, sort, desc, format, output } => {
+            let sina = SINA.lock().await.clone();
+            let since = (Local::now() - chrono::Duration::days(365)).date_naive();
+            let fx = fx_rates(currency, offline).await?;
+            let tasks = split_symbols(&symbol)?.into_iter().map(|symbol| {
+                let sina = sina.clone();
+                async move {
+                    let result = compare(&sina, &symbol, offline, since).await;
+                    (symbol, result)
+                }
+            });
+
+            let mut comparisons = Vec::new();
+            for (symbol, result) in futures_util::future::join_all(tasks).await {
+                match result {
+                    Ok(comparison) => comparisons.push(comparison.convert(fx.as_ref())),
+                    Err(err) => error!("{} {}", symbol, err),
+                }
+            }
+            if let Some(field) = sort {
+                render::sort_by(&mut comparisons, &field, desc)?;
+            }
+
+            let content = render::render(&comparisons, format)?;
+            match output {
+                Some(path) => fs::write(&path, content + "\n")?,
+                None => println!("{}", content),
+            }
+        }
+        Subcommand::Screen { expr, sort, asc, limit, sector, format, output } => {
+            if offline {
+                bail!("离线模式不支持选股");
+            }
+
+            let filter = Filter::parse(&expr)?;
+            let sina = SINA.lock().await.clone();
+            let snapshots = match sector {
+                Some(name) => {
+                    let sectors = sina.sectors().await?;
+                    match sector::find(&sectors, &name) {
+                        Some(sector) => sina.sector_members(sector).await?,
+                        None => bail!("未找到板块 {}", name),
+                    }
+                }
+                None => sina.snapshot().await?,
+            };
+            let total = snapshots.len();
+            let mut results = screener::screen(snapshots, &filter)?;
+            render::sort_by(&mut results, &sort, !asc)?;
+            let matched = results.len();
+            if limit > 0 {
+                results.truncate(limit);
+            }
+
+            let content = render::render(&results, format)?;
+            match output {
+                Some(path) => fs::write(&path, content + "\n")?,
+                None => println!("{}", content),
+            }
+            if format == Format::Table {
+                println!("共 {} 只，符合条件 {} 只", total, matched);
+            }
+        }
+        Subcommand::Sector { action } => {
+            if offline {
+                bail!("离线模式不支持板块查询");
+            }
+
+            let sina = SINA.lock().await.clone();
+            let sectors = sina.sectors().await?;
+            match action {
+                SectorAction::List { kind, perf } => {
+                    let sectors = sectors.into_iter().filter(|s| s.kind == kind).collect::<Vec<_>>();
+                    if !perf {
+                        println!("{}", kind.as_str().bold());
+                        for s in sectors.iter() {
+                            println!("{:<16}\t{}", s.code, s.name);
+                        }
+                        return Ok(());
+                    }
+
+                    let mut perfs = Vec::new();
+                    for chunk in sectors.chunks(4) {
+                        let results =
+                            futures_util::future::join_all(chunk.iter().map(|s| sina.sector_members(s))).await;
+                        for (s, result) in chunk.iter().zip(results) {
+                            match result {
+                                Ok(members) => perfs.push(sector::performance(s, &members)),
+                                Err(err) => error!("{} {}", s.name, err),
                             }
-                        } else {
-                            m.insert(k, i);
                         }
-                        stdout.execute(terminal::Clear(ClearType::CurrentLine)).unwrap();
-                        write_quote(quote);
                     }
-                    stdout.flush().unwrap();
-                    *w = true;
-                })
-                .await;
+                    render::sort_by(&mut perfs, "change_pct", true)?;
+                    println!("{}", render::render(&perfs, Format::Table)?);
+                }
+                SectorAction::Show { name, sort, asc, limit } => {
+                    let sector = match sector::find(&sectors, &name) {
+                        Some(sector) => sector,
+                        None => bail!("未找到板块 {}", name),
+                    };
+                    let mut members = sina.sector_members(sector).await?;
+                    println!("{} {}", sector.kind.as_str(), sector.name.to_string().bold());
+                    println!("{}\n", render::render(&[sector::performance(sector, &members)], Format::Table)?);
+                    render::sort_by(&mut members, &sort, !asc)?;
+                    if limit > 0 {
+                        members.truncate(limit);
+                    }
+                    println!("{}", render::render(&members, Format::Table)?);
+                }
+            }
+        }
+        Subcommand::Market { hk, us, top } => {
+            if offline {
+                bail!("离线模式不支持市场概览");
+            }
+
+            let sina = SINA.lock().await.clone();
+            let indices = match (hk, us) {
+                (true, _) => "HKHSI",
+                (_, true) => "$DJI,$IXIC,$INX",
+                _ => "SH000001,SZ399001,SZ399006,SH000688,BJ899050",
+            };
+            println!("{}", "主要指数".bold());
+            match sina.quotes(indices).await {
+                Ok(quotes) => quotes.iter().for_each(write_quote),
+                Err(err) => error!("{}", err),
+            }
+
+            let snapshots = match (hk, us) {
+                (true, _) => sina.hk_snapshot().await?,
+                (_, true) => sina.us_snapshot().await?,
+                _ => sina.snapshot().await?,
+            };
+            let b = overview::breadth(&snapshots);
+            let limits = if hk || us {
+                String::new()
             } else {
-                match SINA.lock().await.quotes(&symbols).await {
-                    Ok(quotes) => {
-                        for quote in quotes.iter() {
-                            write_quote(quote);
+                format!("  涨停 {}  跌停 {}", b.limit_up.to_string().red(), b.limit_down.to_string().green())
+            };
+            println!(
+                "\n{}\n上涨 {}  下跌 {}  平盘 {}{}  成交额 {}",
+                "涨跌统计".bold(),
+                b.advances.to_string().red(),
+                b.declines.to_string().green(),
+                b.unchanged,
+                limits,
+                fmt_num(&b.volume)
+            );
+            let (gainers, losers, active) = (
+                overview::top(&snapshots, top, |s| s.change_pct, true),
+                overview::top(&snapshots, top, |s| s.change_pct, false),
+                overview::top(&snapshots, top, |s| s.volume, true),
+            );
+            for (title, rows) in [("涨幅榜", gainers), ("跌幅榜", losers), ("成交额榜", active)] {
+                println!("\n{}\n{}", title.bold(), render::render(&rows, Format::Table)?);
+            }
+        }
+        Subcommand::MarketStatus => {
+            let now = Utc::now();
+            for exchange in Exchange::all().iter() {
+                let status = calendar::status(exchange, now);
+                let phase = match status.phase {
+                    Phase::Closed => status.phase.as_str().dark_grey(),
+                    Phase::Break => status.phase.as_str().yellow(),
+                    _ => status.phase.as_str().red(),
+                };
+                let sessions = status
+                    .sessions
+                    .iter()
+                    .map(|s| format!("{}-{} {}", s.start.format("%H:%M"), s.end.format("%H:%M"), s.phase.as_str()))
+                    .collect::<Vec<_>>();
+                println!(
+                    "{}\t{}\t{}\t下次开盘 {}\n\t{}",
+                    exchange.name().bold(),
+                    status.local.format("%Y-%m-%d %a %H:%M %Z"),
+                    phase,
+                    status.next_open.map_or("-".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string()),
+                    if sessions.is_empty() { "今日休市".to_string() } else { sessions.join("  ") }
+                );
+            }
+            if !calendar::is_covered(now.date_naive()) {
+                println!("{}", uncovered_note());
+            }
+        }
+        Subcommand::Presses { action: Some(PressAction::Watch { symbol, keywords, interval, once }), .. } => {
+            if offline {
+                bail!("离线模式不支持公告监控");
+            }
+            let filter = press::Filter { keywords, ..Default::default() };
+            watch_presses(&split_symbols(&symbol)?, &filter, interval, once).await?;
+        }
+        Subcommand::Presses { action: None, symbol, since, until, keywords, pages, download, text, save } => {
+            let symbol = match symbol {
+                Some(symbol) => symbol,
+                None => bail!("请指定证券代码，或使用 presses watch 监控新公告"),
+            };
+            let invest = resolve_symbol(&symbol, offline).await?;
+            match invest.exchange {
+                Some(Exchange::Sse) | Some(Exchange::SZse) | Some(Exchange::Bse) => {}
+                _ => bail!("当前仅支持沪深北证股票公告"),
+            }
+            if offline && download.is_some() {
+                bail!("离线模式不支持下载公告");
+            }
+
+            let symbol = invest.symbol.clone();
+            let filter = press::Filter { since, until, keywords };
+            let mut presses = Vec::new();
+            if offline {
+                presses = Store::open_default()?.presses(&symbol)?;
+            } else {
+                let sina = SINA.lock().await;
+                for page in 1..=pages.max(1) {
+                    let items = sina.presses_page(&symbol[2..], page).await?;
+                    let earlier = items.last().is_none_or(|p| since.is_some_and(|since| p.date < since));
+                    presses.extend(items);
+                    if earlier {
+                        break;
+                    }
+                }
+            }
+            presses.retain(|p| filter.matches(p));
+
+            println!("{} {} 共 {} 条", symbol, invest.name.bold(), presses.len());
+            for p in presses.iter() {
+                println!("{}\t{}\t{}\t{}", p.date, p.category().as_str(), p.title, p.url);
+            }
+
+            if let Some(dir) = download {
+                let dir = PathBuf::from(dir);
+                fs::create_dir_all(&dir)?;
+                let sina = SINA.lock().await;
+                for p in presses.iter_mut() {
+                    let (file, content) = match sina.press_detail(&p.url).await {
+                        Ok(detail) => detail,
+                        Err(err) => {
+                            error!("{} {}", p.title, err);
+                            continue;
                         }
+                    };
+                    p.file = file;
+                    if text && !content.is_empty() {
+                        let path = dir.join(press::filename(p, "txt"));
+                        fs::write(&path, content)?;
+                        println!("已保存 {}", path.display());
+                    }
+                    if p.file.is_empty() {
+                        error!("{} 未找到公告附件", p.title);
+                        continue;
+                    }
+                    let path = dir.join(press::filename(p, "pdf"));
+                    if path.exists() {
+                        println!("已存在 {}", path.display());
+                        continue;
+                    }
+                    match sina.download(&p.file, &path).await {
+                        Ok(size) => println!("已下载 {} {}", path.display(), fmt_size(size)),
+                        Err(err) => error!("{} {}", p.title, err),
                     }
-                    Err(err) => error!("{}", err),
+                }
+                // 仅指定时写入本地数据库 下载本身只写文件
+                if save {
+                    presses.retain(|p| !p.file.is_empty());
+                    let added = Store::open_default()?.save_presses(&symbol, &presses)?;
+                    println!("已保存附件链接 {} 条 新增 {} 条", presses.len(), added);
+                }
+            }
+        }
+        Subcommand::Sync { symbol, sectors } => {
+            if offline {
+                bail!("离线模式不支持同步");
+            }
+
+            let mut store = Store::open_default()?;
+            let mut invests = Vec::new();
+            for symbol in split_symbols(&symbol)? {
+                match check_symbol(&symbol).await {
+                    Ok(invest) => invests.push(invest),
+                    Err(err) => error!("{} {}", symbol, err),
+                }
+            }
+            // 所属板块需遍历全部板块成分股 仅指定时更新 获取失败时保留已同步的
+            let members = if sectors {
+                match SINA.lock().await.sector_symbols().await {
+                    Ok(members) => members,
+                    Err(err) => {
+                        error!("get sectors failed, {}", err);
+                        Vec::new()
+                    }
+                }
+            } else {
+                Vec::new()
+            };
+            for invest in invests.into_iter() {
+                let invest = Investment { sector: sector::classify(&invest.symbol, &members), ..invest };
+                if let Err(err) = sync(&mut store, &invest).await {
+                    error!("{} {}", invest.symbol, err);
                 }
             }
         }
@@ -282,6 +1046,160 @@ async fn run() -> Result<()> {
     Ok(())
 }
 
+/// 拆分代码列表 @name 展开为自选列表
+fn split_symbols(symbols: &str) -> Result<Vec<String>> {
+    let mut results = Vec::new();
+    for symbol in symbols.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        match symbol.strip_prefix('@') {
+            Some(name) => results.extend(store::watchlist(name)?),
+            None => results.push(symbol.to_string()),
+        }
+    }
+
+    Ok(results)
+}
+
+/// 轮询最新公告 首次监控的代码仅记录现有公告 之后提醒新出现且匹配的
+async fn watch_presses(symbols: &[String], filter: &press::Filter, interval: u64, once: bool) -> Result<()> {
+    let mut investments = Vec::new();
+    for symbol in symbols.iter() {
+        match check_symbol(symbol).await {
+            Ok(invest) if matches!(invest.exchange, Some(Exchange::Sse | Exchange::SZse | Exchange::Bse)) => {
+                investments.push(invest)
+            }
+            Ok(invest) => error!("{} 当前仅支持沪深北证股票公告", invest.symbol),
+            Err(err) => error!("{} {}", symbol, err),
+        }
+    }
+    if investments.is_empty() {
+        bail!("无可监控的股票");
+    }
+
+    let mut store = Store::open_default()?;
+    println!(
+        "监控 {} 只股票公告 关键词：{}",
+        investments.len(),
+        if filter.keywords.is_empty() { "全部".to_string() } else { filter.keywords.join(",") }
+    );
+    loop {
+        for invest in investments.iter() {
+            let presses = match SINA.lock().await.presses(&invest.code).await {
+                Ok(presses) => presses,
+                Err(err) => {
+                    error!("{} {}", invest.symbol, err);
+                    continue;
+                }
+            };
+            let seen = store.seen_presses(&invest.symbol)?;
+            let fresh = presses.into_iter().filter(|p| !seen.contains(&p.url)).collect::<Vec<_>>();
+            let started = store.press_watch_since(&invest.symbol)?.is_some();
+            if !started {
+                println!("{} {} 已记录现有公告 {} 条", invest.symbol, invest.name, fresh.len());
+            } else {
+                for p in fresh.iter().filter(|p| filter.matches(p)) {
+                    // 响铃提醒
+                    println!(
+                        "\x07{} {} {} {}\t{}\t{}",
+                        Local::now().format("%H:%M:%S"),
+                        invest.symbol,
+                        invest.name.as_str().bold(),
+                        p.date,
+                        format!("[{}] {}", p.category().as_str(), p.title).red(),
+                        p.url
+                    );
+                }
+            }
+            store.save_seen_presses(&invest.symbol, &fresh)?;
+            if !started {
+                store.save_press_watch_since(&invest.symbol, Local::now().date_naive())?;
+            }
+        }
+
+        if once {
+            break;
+        }
+        select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval.max(10))) => {}
+            _ = signal::ctrl_c() => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// 离线时从本地查找 否则在线检测
+async fn resolve_symbol(symbol: &str, offline: bool) -> Result<Investment> {
+    if !offline {
+        return check_symbol(symbol).await;
+    }
+
+    let invest = Investment::from_str(symbol)?;
+    match Store::open_default()?.investment(&invest.symbol)? {
+        Some(invest) => Ok(invest),
+        None => bail!("本地无该股票数据，请先同步"),
+    }
+}
+
+/// 增量同步K线及公司信息
+async fn sync(store: &mut Store, invest: &Investment) -> Result<()> {
+    match invest.exchange {
+        Some(Exchange::Sse) | Some(Exchange::SZse) | Some(Exchange::Bse) => {}
+        _ => bail!("当前仅支持同步沪深北证股票"),
+    }
+
+    let sina = SINA.lock().await;
+    let symbol = &invest.symbol;
+    let code = &symbol[2..];
+    let profile = sina.profile(symbol).await?;
+    let sector = match &invest.sector {
+        Some(sector) => Some(sector.clone()),
+        None => store.investment(symbol)?.and_then(|i| i.sector),
+    };
+    store.save_investment(&Investment { sector, ..invest.clone() })?;
+    store.save_profile(symbol, &profile)?;
+
+    // 已有数据时仅取最近一段 不连续再全量补齐
+    let bars = match store.last_bar_date(symbol)? {
+        Some(last) => {
+            let bars = sina.bars(symbol, 30).await?;
+            if bars.first().is_some_and(|b| b.date > last) {
+                sina.bars(symbol, 1023).await?
+            } else {
+                bars
+            }
+        }
+        None => sina.bars(symbol, 1023).await?,
+    };
+    let bars = store.save_bars(symbol, &bars)?;
+    let financials = store.save_financials(symbol, &sina.financials(code, 20).await?)?;
+    store.save_balance_sheets(symbol, &sina.balance_sheets(code, 20).await?)?;
+    store.save_income_statements(symbol, &sina.income_statements(code, 20).await?)?;
+    store.save_cash_flows(symbol, &sina.cash_flows(code, 20).await?)?;
+    let structures = store.save_structures(symbol, &sina.structures(code).await?)?;
+    // 股本变动及限售解禁获取失败时不影响其余数据同步
+    let capitals = match sina.capitals(code).await {
+        Ok(capitals) => store.save_capitals(symbol, &capitals)?,
+        Err(err) => {
+            error!("{} get capitals failed, {}", symbol, err);
+            0
+        }
+    };
+    match sina.unlocks(symbol).await {
+        Ok(unlocks) => {
+            store.save_unlocks(symbol, &unlocks)?;
+        }
+        Err(err) => error!("{} get unlocks failed, {}", symbol, err),
+    }
+    let dividends = store.save_dividends(symbol, &sina.dividends(code).await?)?;
+    let presses = store.save_presses(symbol, &sina.presses(code).await?)?;
+
+    println!(
+        "{:<8}\t{}\tK线 +{}\t财务 +{}\t股东 +{}\t股本 +{}\t分红 +{}\t公告 +{}",
+        symbol, invest.name, bars, financials, structures, capitals, dividends, presses
+    );
+    Ok(())
+}
+
 async fn check_symbol(symbol: &str) -> Result<Investment> {
     let invest = match Investment::from_str(symbol) {
         Ok(invest) => invest,
@@ -309,45 +1227,551 @@ async fn check_symbol(symbol: &str) -> Result<Investment> {
     }
 }
 
-fn fmt_num(num: &f64) -> String {
-    match num {
-        _ if *num > 100_000_000.0 => {
-            format!("{:.2}亿", num / 100_000_000.0)
+/// 工商及首发信息 未提供的不显示 公司简介仅全部信息时显示
+fn print_profile_details(profile: &Profile, introduction: bool) {
+    let ipo = &profile.ipo;
+    let num = |n: f64| if n > 0.0 { fmt_num(&n) } else { String::new() };
+    let details = [
+        ("英文名称", profile.english_name.to_string()),
+        ("上市市场", profile.market.to_string()),
+        ("成立日期", profile.founded_date.map_or(String::new(), |d| d.to_string())),
+        ("注册资本", num(profile.registered_capital)),
+        ("机构类型", profile.org_type.to_string()),
+        ("组织形式", profile.org_form.to_string()),
+        ("法人代表", profile.legal_representative.to_string()),
+        ("董事长  ", profile.chairman.to_string()),
+        ("总经理  ", profile.general_manager.to_string()),
+        ("董事会秘书", profile.secretary.to_string()),
+        ("员工人数", if profile.employees > 0.0 { profile.employees.to_string() } else { String::new() }),
+        ("公司电话", profile.phone.to_string()),
+        ("电子邮箱", profile.email.to_string()),
+        ("注册地址", profile.registered_address.to_string()),
+        ("信息披露", profile.disclosure_website.to_string()),
+        ("主承销商", ipo.underwriter.to_string()),
+        ("发行方式", ipo.method.to_string()),
+        ("发行市盈率", if ipo.pe > 0.0 { format!("{:.2}", ipo.pe) } else { String::new() }),
+        ("发行数量", num(ipo.shares)),
+        ("募集资金", num(ipo.proceeds)),
+        ("招股公告", ipo.prospectus_date.map_or(String::new(), |d| d.to_string())),
+    ];
+    for (label, value) in details.iter().filter(|(_, v)| !v.is_empty() && v != "--") {
+        println!("{}\t{}", label, value);
+    }
+    if introduction && !profile.introduction.is_empty() {
+        println!("公司简介\t{}", profile.introduction);
+    }
+}
+
+/// 在任高管、董事会及监事会成员
+fn print_managers(profile: &Profile) {
+    let today = Local::now().date_naive();
+    for (title, managers) in
+        [("高管", &profile.executives), ("董事会", &profile.directors), ("监事会", &profile.supervisors)]
+    {
+        let current = managers.iter().filter(|m| m.is_current(today)).collect::<Vec<_>>();
+        if current.is_empty() {
+            continue;
+        }
+        println!("\n{}", title.bold());
+        for m in current {
+            println!("{}\t{}\t{}起", m.name, m.title, m.start.map_or(" - ".to_string(), |d| d.to_string()));
+        }
+    }
+}
+
+/// 股息率、支付率、连续分红年数及派息增长 之后为年度汇总
+fn print_dividend_summary(summary: &dividend::Summary) {
+    println!(
+        "近一年股息率 {} \t 股利支付率 {} \t 连续分红 {}年 \t 每股派息5年复合增长 {}",
+        fmt_rate(summary.yield_ttm),
+        fmt_rate(summary.payout_ratio),
+        summary.consecutive_years,
+        fmt_rate(summary.dps_cagr)
+    );
+    if summary.years.is_empty() {
+        return;
+    }
+    println!("年度 \t 每股派息 \t 每10股送转 \t 股利支付率");
+    for y in summary.years.iter() {
+        println!(
+            "{} \t {:.4} \t {} \t\t {}",
+            y.year,
+            y.dps,
+            match y.shares_dividend + y.shares_into {
+                n if n > 0.0 => format!("{:.2}", n),
+                _ => " - ".to_string(),
+            },
+            fmt_rate(y.payout_ratio)
+        );
+    }
+}
+
+/// 股东户数趋势 最新一期十大股东、十大流通股东及基金持股
+fn print_structure(structures: &[Structure]) {
+    println!("截止日期 \t 股东户数 \t 环比 \t\t 平均持股");
+    for (s, change) in structures.iter().zip(holder::holders_num_change(structures)) {
+        let change = change.map_or(" - ".to_string(), fmt_rate);
+        println!("{} \t {} \t {:<8} \t {}", s.date, fmt_num(&s.holders_num), change, fmt_num(&s.shares_avg));
+    }
+
+    let first = structures.first().unwrap();
+    for (title, holders) in [("十大股东", &first.holders_ten), ("十大流通股东", &first.holders_float)] {
+        if holders.is_empty() {
+            continue;
+        }
+        println!("\n{}({})", title, first.date);
+        for (i, h) in holders.iter().enumerate() {
+            println!("{}\t{}({}% {})", i + 1, h.name, h.percent, fmt_num(&h.shares))
+        }
+    }
+
+    if !first.funds.is_empty() {
+        let shares = first.funds.iter().map(|f| f.shares).sum::<f64>();
+        let percent = first.funds.iter().map(|f| f.percent).sum::<f64>();
+        println!("\n基金持股({}) {}家 合计{}({:.2}%)", first.date, first.funds.len(), fmt_num(&shares), percent);
+        let mut funds = first.funds.iter().collect::<Vec<_>>();
+        funds.sort_by(|a, b| b.shares.total_cmp(&a.shares));
+        for (i, f) in funds.iter().take(10).enumerate() {
+            println!("{}\t{} {}({}% {})", i + 1, f.code, f.name, f.percent, fmt_num(&f.shares))
         }
-        _ if *num == 0.0 => " - ".to_string(),
+    }
+}
+
+/// 对比最近两期 新进/增持标红 退出/减持标绿
+fn print_holders_diff(structures: &[Structure]) {
+    let (cur, prev) = match structures {
+        [cur, prev, ..] => (cur, prev),
         _ => {
-            format!("{:.2}万", num / 10_000.0)
+            println!("股东数据不足两期");
+            return;
+        }
+    };
+    for (title, cur_holders, prev_holders) in
+        [("十大股东", &cur.holders_ten, &prev.holders_ten), ("十大流通股东", &cur.holders_float, &prev.holders_float)]
+    {
+        if cur_holders.is_empty() || prev_holders.is_empty() {
+            continue;
+        }
+        println!("\n{}变动({} 对比 {})", title, cur.date, prev.date);
+        for c in holder::diff(prev_holders, cur_holders) {
+            let change = format!("{}\t{}\t{}({})", c.change.as_str(), c.name, fmt_num(&c.shares), fmt_num(&c.delta));
+            match c.change {
+                Change::New | Change::Increased => println!("{}", change.red()),
+                Change::Exited | Change::Decreased => println!("{}", change.green()),
+                Change::Unchanged => println!("{}", change),
+            }
+        }
+    }
+}
+
+/// 按行输出 首列为名称 之后每期一列
+fn write_rows(rows: &[(&str, Vec<String>)]) {
+    // align todo change
+    for (name, values) in rows.iter() {
+        let mut output = format!("{:<16}", name);
+        for v in values.iter() {
+            output.push_str(&format!("\t{:<16}", v));
+        }
+        println!("{}", output);
+    }
+}
+
+/// 指定币种时获取汇率
+/// 在线获取后缓存到本地 离线时使用最近一次的汇率
+async fn fx_rates(currency: Option<Currency>, offline: bool) -> Result<Option<(Rates, Currency)>> {
+    let currency = match currency {
+        Some(currency) => currency,
+        None => return Ok(None),
+    };
+    if offline {
+        return match Store::open_default()?.rates()? {
+            Some(rates) => Ok(Some((rates, currency))),
+            None => bail!("本地无汇率数据，请先在线使用 --currency"),
+        };
+    }
+
+    let rates = SINA.lock().await.rates().await?;
+    if let Err(err) = Store::open_default().and_then(|mut store| store.save_rates(&rates, Local::now().date_naive())) {
+        error!("save rates failed, {}", err);
+    }
+    Ok(Some((rates, currency)))
+}
+
+fn convert_profile(profile: Profile, fx: Option<&(Rates, Currency)>) -> Profile {
+    match fx {
+        Some((rates, to)) => profile.convert(rates, *to).unwrap_or(profile),
+        None => profile,
+    }
+}
+
+/// 获取单个股票的对比数据 since: 股息率统计起始日
+async fn compare(sina: &Sina, symbol: &str, offline: bool, since: NaiveDate) -> Result<Comparison> {
+    let invest = resolve_symbol(symbol, offline).await?;
+    match invest.exchange {
+        Some(Exchange::Sse) | Some(Exchange::SZse) | Some(Exchange::Bse) => {}
+        _ => bail!("当前仅支持沪深北证股票对比"),
+    }
+
+    let (profile, financials, dividends) = if offline {
+        let store = Store::open_default()?;
+        (store.profile(&invest.symbol)?, store.financials(&invest.symbol)?, store.dividends(&invest.symbol)?)
+    } else {
+        let code = &invest.symbol[2..];
+        let (profile, financials, dividends) = futures_util::join!(
+            sina.profile(&invest.symbol),
+            sina.financials(code, Period::Cumulative.required(1)),
+            sina.dividends(code)
+        );
+        (profile?, financials?, dividends?)
+    };
+
+    Ok(Comparison::new(&invest, &profile, financials.first(), &dividends, since))
+}
+
+/// 多股对比
+#[derive(Debug, Serialize)]
+struct Comparison {
+    symbol: String,
+    name: String,
+    price: f64,
+    market_cap: f64,
+    currency: Currency,
+    pe_ttm: f64,
+    pb: f64,
+    /// 最近一期报告期
+    date: Option<NaiveDate>,
+    revenue_growth: f64,
+    net_profit_growth: f64,
+    roe: f64,
+    /// 近一年股息率
+    dividend_yield: f64,
+}
+
+impl Comparison {
+    fn new(
+        invest: &Investment,
+        profile: &Profile,
+        financial: Option<&Financial>,
+        dividends: &[Dividend],
+        since: NaiveDate,
+    ) -> Self {
+        let date = financial.map(|f| f.date);
+        let financial = financial.cloned().unwrap_or_default();
+        Comparison {
+            symbol: invest.symbol.to_string(),
+            name: invest.name.to_string(),
+            price: profile.price,
+            market_cap: profile.market_cap,
+            currency: profile.currency,
+            pe_ttm: profile.pe_ttm,
+            pb: profile.pb,
+            date,
+            revenue_growth: financial.total_revenue_rate,
+            net_profit_growth: financial.net_profit_rate,
+            roe: financial.roe,
+            dividend_yield: ratio::dividend_yield(dividends, profile.price, since),
+        }
+    }
+}
+
+impl Comparison {
+    /// 股息率按原币种计算后再换算价格及市值
+    fn convert(self, fx: Option<&(Rates, Currency)>) -> Self {
+        match fx.and_then(|(rates, to)| {
+            Some((
+                rates.convert(self.price, self.currency, *to)?,
+                rates.convert(self.market_cap, self.currency, *to)?,
+                *to,
+            ))
+        }) {
+            Some((price, market_cap, currency)) => Comparison { price, market_cap, currency, ..self },
+            None => self,
         }
     }
 }
 
+impl Row for Comparison {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "代码",
+            "名称",
+            "价格",
+            "总市值",
+            "币种",
+            "市盈率TTM",
+            "市净率",
+            "报告期",
+            "营收同比",
+            "净利润同比",
+            "净资产收益率",
+            "股息率",
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.symbol.to_string(),
+            self.name.to_string(),
+            format!("{:.2}", self.price),
+            fmt_num(&self.market_cap),
+            self.currency.as_str().to_string(),
+            format!("{:.2}", self.pe_ttm),
+            format!("{:.2}", self.pb),
+            self.date.map_or(" - ".to_string(), |d| d.to_string()),
+            fmt_rate(self.revenue_growth),
+            fmt_rate(self.net_profit_growth),
+            fmt_rate(self.roe),
+            fmt_rate(self.dividend_yield),
+        ]
+    }
+}
+
+fn uncovered_note() -> String {
+    format!("节假日表仅覆盖 {}-{} 年，其他年份仅按周末判断", calendar::YEARS.0, calendar::YEARS.1)
+}
+
+/// 相关交易所均休市时等待至最早开盘 Ctrl-C 返回 false
+///
+/// 开盘时间超出节假日表覆盖年份时无法确定 不等待
+async fn wait_open(exchanges: &[Exchange]) -> bool {
+    let now = Utc::now();
+    let statuses = exchanges.iter().map(|ex| (ex, calendar::status(ex, now))).collect::<Vec<_>>();
+    if statuses.is_empty() || statuses.iter().any(|(_, s)| s.phase != Phase::Closed) {
+        return true;
+    }
+
+    let next = match statuses
+        .iter()
+        .filter_map(|(ex, s)| s.next_open.map(|t| (ex, t)))
+        .min_by_key(|(_, t)| t.with_timezone(&Utc))
+    {
+        Some(next) => next,
+        None => return true,
+    };
+    if !calendar::is_covered(next.1.date_naive()) {
+        warn!("{}，无法确定下次开盘时间，不等待", uncovered_note());
+        return true;
+    }
+    let wait = (next.1.with_timezone(&Utc) - now).to_std().unwrap_or_default();
+    println!(
+        "休市中，{} 下次开盘 {}（本地 {}），等待中... Ctrl-C 退出",
+        next.0.name(),
+        next.1.format("%Y-%m-%d %H:%M %Z"),
+        next.1.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    );
+    select! {
+        _ = tokio::time::sleep(wait) => true,
+        _ = signal::ctrl_c() => false,
+    }
+}
+
+/// 渲染实时行情直至结束或 Ctrl-C
+async fn watch(mut board: Board, mut stream: QuoteStream) {
+    loop {
+        select! {
+            event = stream.next() => match event {
+                Some(event) => board.render(event),
+                None => break,
+            },
+            _ = signal::ctrl_c() => break,
+        }
+    }
+}
+
+/// 实时行情看板 每个代码固定一行 仅重绘收到的行
+#[derive(Debug, Default)]
+struct Board {
+    /// 单个代码时逐条输出
+    multiline: bool,
+    /// 每行展示的逐笔成交条数
+    show_ticks: usize,
+    /// 逐笔成交追加写入
+    tick_file: Option<File>,
+    /// 距涨跌停标记阈值 %
+    near: f64,
+    /// 币种换算
+    fx: Option<(Rates, Currency)>,
+    ticks: TickLog,
+    /// 代码对应行号
+    lines: HashMap<String, usize>,
+    /// 最后写入的行号
+    cur: usize,
+}
+
+impl Board {
+    fn render(&mut self, event: QuoteEvent) {
+        let quotes = match event {
+            QuoteEvent::Quotes(quotes) => quotes,
+            event => return self.status(event),
+        };
+
+        if self.multiline && quotes.len() == 1 && self.lines.is_empty() {
+            println!("{}", self.row(quotes.first().unwrap()));
+            return;
+        }
+
+        for quote in quotes.iter() {
+            let row = self.row(quote);
+            self.write_line(&quote.symbol, &row);
+        }
+        stdout().flush().unwrap();
+    }
+
+    /// 连接状态 看板中固定占一行 断线及降级时提醒
+    fn status(&mut self, event: QuoteEvent) {
+        let time = Local::now().format("%H:%M:%S");
+        let text = match event {
+            QuoteEvent::Connected => format!("{} 行情已连接", time).green(),
+            QuoteEvent::Disconnected(reason) => format!("{} 行情连接断开 {}", time, reason).yellow(),
+            QuoteEvent::Reconnecting { attempt, delay } => {
+                format!("{} 第 {} 次重连 {}秒后重试", time, attempt, delay.as_secs()).yellow()
+            }
+            QuoteEvent::Stale(timeout) => format!("{} 超过 {}秒未收到行情 重连中", time, timeout.as_secs()).red(),
+            QuoteEvent::Polling => format!("{} 实时连接失败 已降级为轮询", time).red(),
+            QuoteEvent::Quotes(_) => return,
+        };
+        if self.multiline {
+            println!("{}", text);
+        } else {
+            // 状态行键名不会与代码重复
+            self.write_line("", &text.to_string());
+            stdout().flush().unwrap();
+        }
+    }
+
+    /// 移动至 key 所在行重写 新 key 追加一行
+    fn write_line(&mut self, key: &str, text: &str) {
+        let mut stdout = stdout();
+        let written = !self.lines.is_empty();
+        let next = self.lines.len();
+        let line = *self.lines.entry(key.to_string()).or_insert(next);
+        if written {
+            if self.cur >= line {
+                stdout.execute(cursor::MoveToPreviousLine((self.cur - line + 1) as u16)).unwrap();
+            } else if line > self.cur + 1 {
+                stdout.execute(cursor::MoveToNextLine((line - self.cur - 1) as u16)).unwrap();
+            }
+        }
+        self.cur = line;
+        stdout.execute(terminal::Clear(ClearType::CurrentLine)).unwrap();
+        println!("{}", text);
+    }
+
+    /// 记录逐笔成交 并高亮与上次相比变化的字段
+    fn row(&mut self, quote: &Quote) -> String {
+        let prev = self.ticks.last(&quote.symbol).cloned();
+        if let Some(tick) = self.ticks.push(quote) {
+            if let Some(file) = self.tick_file.as_mut() {
+                if let Err(err) = writeln!(
+                    file,
+                    "{},{},{},{},{},{}",
+                    tick.symbol,
+                    tick.time.format("%Y-%m-%d,%H:%M:%S"),
+                    tick.price,
+                    tick.turnover,
+                    tick.volume,
+                    tick.side.as_str()
+                ) {
+                    error!("write tick failed, {}", err);
+                }
+            }
+        }
+
+        let mut row = fmt_quote(quote, prev.as_ref(), self.near, self.fx.as_ref());
+        if self.show_ticks > 0 {
+            let ticks = self
+                .ticks
+                .ticks(&quote.symbol)
+                .map(|t| {
+                    let tick = format!("{:.2}/{}{}", t.price, t.turnover, t.side.as_str());
+                    match t.side {
+                        Side::Buy => tick.red().to_string(),
+                        Side::Sell => tick.green().to_string(),
+                        Side::Neutral => tick,
+                    }
+                })
+                .collect::<Vec<String>>();
+            row.push_str(&format!("\t逐笔：{}", ticks.join(" ")));
+        }
+
+        row
+    }
+}
+
 fn write_quote(quote: &Quote) {
+    println!("{}", fmt_quote(quote, None, NEAR_LIMIT, None));
+}
+
+/// prev: 上次行情 变化的字段按涨跌反色高亮 near: 距涨跌停在该百分比以内时标记 fx: 换算币种
+fn fmt_quote(quote: &Quote, prev: Option<&Quote>, near: f64, fx: Option<&(Rates, Currency)>) -> String {
+    // 涨跌停按原币种判断 避免换算后的舍入误差
+    let status = quote.limit_status(near);
+    let (quote, prev) = match fx {
+        Some((rates, to)) => {
+            (&quote.convert(rates, *to).unwrap_or_else(|| quote.clone()), prev.and_then(|p| p.convert(rates, *to)))
+        }
+        None => (quote, prev.cloned()),
+    };
+    let prev = prev.as_ref();
     let rate = (quote.now / quote.close - 1.0) * 100.0;
     let now = format!("{:.2} {:.2}%", quote.now, rate);
     // 港股指数成交额 * 1000
     let volume =
         if Regex::new("HK([A-Z]{3})").unwrap().is_match(&quote.symbol) { quote.volume * 1000.0 } else { quote.volume };
+    let now = match rate {
+        _ if rate > 0.0 => now.red(),
+        _ if rate < 0.0 => now.green(),
+        _ => now.dark_grey(),
+    }
+    .bold()
+    .underline()
+    .to_string();
+    let now = match prev {
+        Some(prev) if prev.now != quote.now => now.reverse().to_string(),
+        _ => now,
+    };
 
-    println!(
-        "{} {}  {:<8}  {:<16} \t昨收：{:.2}\t今开：{:.2}\t最高：{:.2}\t最低：{:.2}\t成交量：{:<8}\t成交额：{:<8}\t{}",
-        quote.date,
-        quote.time,
+    let row = format!(
+        "{}  {:<8}  {:<16} \t昨收：{:.2}\t今开：{:.2}\t最高：{}\t最低：{}\t成交量：{:<8}\t成交额：{:<8}\t{}",
+        fmt_time(quote.time.as_ref()),
         quote.symbol,
-        match rate {
-            _ if rate > 0.0 => now.red(),
-            _ if rate < 0.0 => now.green(),
-            _ => now.dark_grey(),
-        }
-        .bold()
-        .underline(),
+        now,
         quote.close,
         quote.open,
-        quote.high,
-        quote.low,
-        fmt_num(&quote.turnover),
-        fmt_num(&volume),
-        quote.name,
+        flash(format!("{:.2}", quote.high), quote.high, prev.map(|p| p.high)),
+        flash(format!("{:.2}", quote.low), quote.low, prev.map(|p| p.low)),
+        flash(fmt_num(&quote.turnover), quote.turnover, prev.map(|p| p.turnover)),
+        flash(fmt_num(&volume), quote.volume, prev.map(|p| p.volume)),
+        if quote.currency == Currency::Cny {
+            quote.name.to_string()
+        } else {
+            format!("{} {}", quote.name, quote.currency.as_str())
+        },
     );
+    match status {
+        Some(status @ (LimitStatus::LimitUp | LimitStatus::NearLimitUp)) => {
+            format!("{}\t{}", row, status.as_str().on_red())
+        }
+        Some(status) => format!("{}\t{}", row, status.as_str().on_green()),
+        None => row,
+    }
+}
+
+/// 文件大小 eg: 1.25MB
+fn fmt_size(size: usize) -> String {
+    match size as f64 {
+        s if s >= 1024.0 * 1024.0 => format!("{:.2}MB", s / 1024.0 / 1024.0),
+        s => format!("{:.2}KB", s / 1024.0),
+    }
+}
+
+/// 较上次变大红底 变小绿底
+fn flash(text: String, cur: f64, prev: Option<f64>) -> String {
+    match prev {
+        Some(prev) if cur > prev => text.on_red().to_string(),
+        Some(prev) if cur < prev => text.on_green().to_string(),
+        _ => text,
+    }
 }
 
 #[cfg(test)]
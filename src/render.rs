@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Error, Result};
+use chrono::{DateTime, FixedOffset, Local, Offset};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::invest::{quote::Snapshot, sector::SectorPerformance};
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Format {
+    /// 表格
+    Table,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => bail!("不支持的输出格式：{}", s),
+        }
+    }
+}
+
+/// 可渲染为表格的记录 JSON/CSV 按字段原值输出
+pub trait Row: Serialize {
+    /// 表头 与 cells 一一对应
+    fn headers() -> Vec<&'static str>;
+    /// 表格展示值
+    fn cells(&self) -> Vec<String>;
+}
+
+pub fn render<T: Row>(rows: &[T], format: Format) -> Result<String> {
+    match format {
+        Format::Table => {
+            let mut lines = vec![fmt_line(T::headers().iter().map(|h| h.to_string()))];
+            lines.extend(rows.iter().map(|row| fmt_line(row.cells().into_iter())));
+            Ok(lines.join("\n"))
+        }
+        Format::Json => Ok(serde_json::to_string_pretty(rows)?),
+        Format::Csv => {
+            let mut lines = Vec::new();
+            for row in rows.iter() {
+                let fields = match serde_json::to_value(row)? {
+                    Value::Object(fields) => fields,
+                    _ => bail!("仅支持结构体导出 CSV"),
+                };
+                if lines.is_empty() {
+                    lines.push(fields.keys().map(|k| csv_field(k)).collect::<Vec<_>>().join(","));
+                }
+                lines.push(
+                    fields
+                        .values()
+                        .map(|v| match v {
+                            Value::Null => String::new(),
+                            Value::String(s) => csv_field(s),
+                            v => csv_field(&v.to_string()),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(","),
+                );
+            }
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+/// 按字段排序 字段须为数值或字符串
+pub fn sort_by<T: Serialize>(rows: &mut Vec<T>, field: &str, desc: bool) -> Result<()> {
+    let mut keys = Vec::new();
+    for row in rows.iter() {
+        match serde_json::to_value(row)?.get(field) {
+            Some(v @ (Value::Number(_) | Value::String(_))) => keys.push(v.clone()),
+            _ => bail!("不支持按 {} 排序", field),
+        }
+    }
+    let mut keyed = keys.into_iter().zip(rows.drain(..)).collect::<Vec<_>>();
+
+    keyed.sort_by(|(a, _), (b, _)| {
+        let ord = match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+            _ => a.as_str().cmp(&b.as_str()),
+        };
+        if desc {
+            ord.reverse()
+        } else {
+            ord
+        }
+    });
+    rows.extend(keyed.into_iter().map(|(_, row)| row));
+
+    Ok(())
+}
+
+impl Row for Snapshot {
+    fn headers() -> Vec<&'static str> {
+        vec!["代码", "名称", "当前价", "涨跌幅", "成交额", "换手率", "市盈率(动)", "市净率", "总市值", "流通市值"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.symbol.to_string(),
+            self.name.to_string(),
+            format!("{:.2}", self.price),
+            fmt_rate(self.change_pct),
+            fmt_num(&self.volume),
+            fmt_rate(self.turnover_rate),
+            format!("{:.2}", self.pe),
+            format!("{:.2}", self.pb),
+            fmt_num(&self.market_cap),
+            fmt_num(&self.traded_market_cap),
+        ]
+    }
+}
+
+impl Row for SectorPerformance {
+    fn headers() -> Vec<&'static str> {
+        vec!["代码", "名称", "成分股", "涨跌幅", "平均涨跌幅", "上涨", "下跌", "成交额", "总市值", "领涨股"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.code.to_string(),
+            self.name.to_string(),
+            self.total.to_string(),
+            fmt_rate(self.change_pct),
+            fmt_rate(self.avg_change_pct),
+            self.advances.to_string(),
+            self.declines.to_string(),
+            fmt_num(&self.volume),
+            fmt_num(&self.market_cap),
+            format!("{} {}", self.leader, fmt_rate(self.leader_change_pct)),
+        ]
+    }
+}
+
+pub fn fmt_rate(rate: f64) -> String {
+    if rate == 0.0 {
+        " - ".to_string()
+    } else {
+        format!("{:.2}%", rate)
+    }
+}
+
+pub fn fmt_num(num: &f64) -> String {
+    match num {
+        _ if num.abs() > 100_000_000.0 => {
+            format!("{:.2}亿", num / 100_000_000.0)
+        }
+        _ if *num == 0.0 => " - ".to_string(),
+        _ => {
+            format!("{:.2}万", num / 10_000.0)
+        }
+    }
+}
+
+/// 交易所当地时间 与本地时区不同时附本地时间 未知时间为 -
+pub fn fmt_time(time: Option<&DateTime<FixedOffset>>) -> String {
+    let time = match time {
+        Some(time) => time,
+        None => return " - ".to_string(),
+    };
+    let local = time.with_timezone(&Local);
+    if local.offset().fix() == *time.offset() {
+        time.format("%Y-%m-%d %H:%M:%S").to_string()
+    } else {
+        format!("{}(本地 {})", time.format("%Y-%m-%d %H:%M:%S"), local.format("%m-%d %H:%M"))
+    }
+}
+
+fn fmt_line(cells: impl Iterator<Item = String>) -> String {
+    cells.map(|c| format!("{:<16}", c)).collect::<Vec<_>>().join("\t")
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Item {
+        name: String,
+        value: f64,
+    }
+
+    impl Row for Item {
+        fn headers() -> Vec<&'static str> {
+            vec!["名称", "值"]
+        }
+
+        fn cells(&self) -> Vec<String> {
+            vec![self.name.to_string(), format!("{:.1}", self.value)]
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut items = vec![Item { name: "a,b".to_string(), value: 2.0 }, Item { name: "c".to_string(), value: 10.5 }];
+        sort_by(&mut items, "value", true).unwrap();
+        assert_eq!(items[0].name, "c");
+        assert!(sort_by(&mut items, "other", false).is_err());
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(render(&items, Format::Csv).unwrap(), "name,value\nc,10.5\n\"a,b\",2.0");
+        assert!(render(&items, Format::Json).unwrap().starts_with("[\n  {\n    \"name\": \"c\""));
+    }
+}
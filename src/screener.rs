@@ -0,0 +1,260 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// 选股条件表达式
+///
+/// 支持字段名、数字(含 1e10 科学计数)、四则运算、比较 > >= < <= == != 、&& || ! 及括号
+/// eg: pe < 15 && market_cap > 1e10 && change_pct > 2
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Num(f64),
+    Field(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, Op, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Or,
+    And,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(Op),
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!("表达式解析失败，多余的 {:?}", token);
+        }
+        Ok(Filter { expr })
+    }
+
+    /// 按记录的序列化字段求值 未知字段报错
+    pub fn matches<T: Serialize>(&self, row: &T) -> Result<bool> {
+        match serde_json::to_value(row)? {
+            Value::Object(fields) => Ok(eval(&self.expr, &fields)? != 0.0),
+            _ => bail!("仅支持结构体筛选"),
+        }
+    }
+}
+
+/// 筛选符合条件的记录
+pub fn screen<T: Serialize>(rows: Vec<T>, filter: &Filter) -> Result<Vec<T>> {
+    let mut results = Vec::new();
+    for row in rows.into_iter() {
+        if filter.matches(&row)? {
+            results.push(row);
+        }
+    }
+    Ok(results)
+}
+
+fn eval(expr: &Expr, fields: &Map<String, Value>) -> Result<f64> {
+    let bool_num = |b: bool| if b { 1.0 } else { 0.0 };
+    Ok(match expr {
+        Expr::Num(n) => *n,
+        Expr::Field(name) => match fields.get(name) {
+            Some(Value::Number(n)) => n.as_f64().unwrap_or(0.0),
+            Some(Value::Bool(b)) => bool_num(*b),
+            Some(_) => bail!("字段 {} 不是数值", name),
+            None => bail!("未知字段 {}，可用字段: {}", name, fields.keys().cloned().collect::<Vec<_>>().join(" ")),
+        },
+        Expr::Not(e) => bool_num(eval(e, fields)? == 0.0),
+        Expr::Neg(e) => -eval(e, fields)?,
+        Expr::Binary(l, Op::And, r) => bool_num(eval(l, fields)? != 0.0 && eval(r, fields)? != 0.0),
+        Expr::Binary(l, Op::Or, r) => bool_num(eval(l, fields)? != 0.0 || eval(r, fields)? != 0.0),
+        Expr::Binary(l, op, r) => {
+            let (l, r) = (eval(l, fields)?, eval(r, fields)?);
+            match op {
+                Op::Gt => bool_num(l > r),
+                Op::Ge => bool_num(l >= r),
+                Op::Lt => bool_num(l < r),
+                Op::Le => bool_num(l <= r),
+                Op::Eq => bool_num(l == r),
+                Op::Ne => bool_num(l != r),
+                Op::Add => l + r,
+                Op::Sub => l - r,
+                Op::Mul => l * r,
+                Op::Div if r == 0.0 => 0.0,
+                Op::Div => l / r,
+                Op::And | Op::Or => unreachable!(),
+            }
+        }
+    })
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>> {
+    let chars = expr.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::Op(Op::And), 2),
+            ('|', Some('|')) => (Token::Op(Op::Or), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('+', _) => (Token::Op(Op::Add), 1),
+            ('-', _) => (Token::Op(Op::Sub), 1),
+            ('*', _) => (Token::Op(Op::Mul), 1),
+            ('/', _) => (Token::Op(Op::Div), 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            _ if c.is_ascii_digit() || c == '.' => {
+                let mut end = i;
+                while end < chars.len()
+                    && (chars[end].is_ascii_digit()
+                        || chars[end] == '.'
+                        || matches!(chars[end], 'e' | 'E')
+                        || (matches!(chars[end], '+' | '-') && matches!(chars[end - 1], 'e' | 'E')))
+                {
+                    end += 1;
+                }
+                let num = chars[i..end].iter().collect::<String>();
+                match num.parse::<f64>() {
+                    Ok(n) => (Token::Num(n), end - i),
+                    Err(_) => bail!("无效数字 {}", num),
+                }
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = i;
+                while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+                (Token::Ident(chars[i..end].iter().collect()), end - i)
+            }
+            _ => bail!("表达式包含无效字符 {}", c),
+        };
+        tokens.push(token);
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Expr> {
+        self.binary(&[Op::Or], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        self.binary(&[Op::And], Self::compare)
+    }
+
+    fn compare(&mut self) -> Result<Expr> {
+        self.binary(&[Op::Gt, Op::Ge, Op::Lt, Op::Le, Op::Eq, Op::Ne], Self::sum)
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        self.binary(&[Op::Add, Op::Sub], Self::term)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        self.binary(&[Op::Mul, Op::Div], Self::unary)
+    }
+
+    fn binary(&mut self, ops: &[Op], next: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        let mut expr = next(self)?;
+        while let Some(Token::Op(op)) = self.tokens.get(self.pos) {
+            if !ops.contains(op) {
+                break;
+            }
+            let op = *op;
+            self.pos += 1;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(next(self)?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            Some(Token::Ident(name)) => Ok(Expr::Field(name)),
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Op(Op::Sub)) => Ok(Expr::Neg(Box::new(self.unary()?))),
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::RParen) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => bail!("表达式解析失败，缺少 )"),
+                }
+            }
+            Some(token) => bail!("表达式解析失败，意外的 {:?}", token),
+            None => bail!("表达式解析失败，表达式不完整"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::quote::Snapshot;
+
+    #[test]
+    fn test_filter() {
+        let filter = Filter::parse("pe > 0 && pe < 15 && market_cap > 1e10 && change_pct > 2").unwrap();
+        let item = Snapshot { pe: 8.0, market_cap: 2e10, change_pct: 2.5, ..Default::default() };
+        assert!(filter.matches(&item).unwrap());
+        assert!(!filter.matches(&Snapshot { change_pct: -1.0, ..item.clone() }).unwrap());
+        assert_eq!(screen(vec![item.clone(), Snapshot { pe: -3.0, ..item }], &filter).unwrap().len(), 1);
+        // 快照仅有动态市盈率
+        assert!(Filter::parse("pe_ttm < 15").unwrap().matches(&Snapshot::default()).is_err());
+
+        let item = Snapshot { pe: 20.0, market_cap: 5e9, change_pct: -3.0, ..Default::default() };
+        assert!(Filter::parse("!(pe < 15) || market_cap / 1e8 >= 100").unwrap().matches(&item).unwrap());
+        assert!(Filter::parse("-change_pct == 1 + 2 * 1").unwrap().matches(&item).unwrap());
+        assert!(!Filter::parse("price >= limit_up && limit_up > 0").unwrap().matches(&item).unwrap());
+        assert!(Filter::parse("roe > 1").unwrap().matches(&item).is_err());
+        assert!(Filter::parse("pe_ttm < ").is_err());
+        assert!(Filter::parse("(pe_ttm < 1").is_err());
+        assert!(Filter::parse("pe_ttm ~ 1").is_err());
+    }
+}
@@ -1,30 +1,72 @@
 // todo source provider
 
-use std::{f64, str::FromStr, time::Duration};
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    f64,
+    fs::{self, File, OpenOptions},
+    future::Future,
+    io::Write,
+    path::{Path, PathBuf},
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::{bail, Result};
-use futures_util::{SinkExt, StreamExt};
+use chrono::{Datelike, NaiveDate, Utc};
+use futures_util::{future::join_all, SinkExt, Stream, StreamExt};
 use http::{Method, Request};
+use once_cell::sync::Lazy;
 use regex::{Captures, Regex};
 use reqwest::{
     header::{self, HeaderMap, HeaderValue},
-    Client, StatusCode,
+    Client, StatusCode, Url,
 };
 use scraper::{ElementRef, Html, Node, Selector};
-use tokio::{join, select, time::interval};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    join, select,
+    sync::mpsc,
+    task::JoinHandle,
+    time::{interval, sleep, Instant},
+};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error};
 
-use crate::invest::{
-    fmt_us_symbol,
-    quote::Quote,
-    stock::{Dividend, Financial, Holder, Press, Profile, Structure},
-    Exchange, Investment, Market,
+use crate::{
+    invest::{
+        calendar::{self, parse_date, Phase},
+        currency::{Currency, Rates},
+        fmt_us_symbol, limit_prices,
+        quote::{Bar, Quote, QuoteEvent, Snapshot},
+        ratio,
+        sector::{Sector, SectorKind},
+        statement::{BalanceSheet, CashFlow, IncomeStatement},
+        stock::{
+            financials_by_period, Capital, Dividend, Financial, Fund, Holder, Manager, Period, Press, Profile,
+            Structure, Unlock,
+        },
+        Exchange, Investment, Market,
+    },
+    table::{normalize_label, LayoutError, Record, Table},
 };
 
 const PORTAL: &str = "https://finance.sina.com.cn";
+/// 行情单次请求代码数
+const QUOTES_BATCH: usize = 100;
+/// 行情中心列表每页条数
+const SNAPSHOT_PAGE: usize = 100;
+/// 行情中心列表并发请求数
+const SNAPSHOT_CONCURRENCY: usize = 8;
+const MARKET_CENTER: &str = "https://vip.stock.finance.sina.com.cn/quotes_service/api/json_v2.php/Market_Center";
+/// 美股指数 新浪代码带 $ 前缀 eg: gb_$dji
+const US_INDICES: [&str; 2] = ["dji", "inx"];
+const HTTP_QUOTES_PATTERN: &str = "hq_str_(?:rt_)?(?:gb_)?\\$?([A-Za-z0-9]+)=\"(.*)\"";
+const WS_QUOTES_PATTERN: &str = "(?:rt_)?(?:gb_)?([A-Za-z0-9]+)=(.*)\\n";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Sina {
     client: Client,
 }
@@ -97,10 +139,7 @@ impl Sina {
                             match *v.get(1).unwrap() {
                                 "11" | "12" | "15" => {
                                     market = Some(Market::Stock);
-                                    exchange = match Exchange::from_str(&symbol[..2]) {
-                                        Ok(ex) => Some(ex),
-                                        Err(_) => None,
-                                    }
+                                    exchange = Exchange::from_str(&symbol[..2]).ok()
                                 }
                                 "21" | "22" | "23" | "24" | "25" | "26" => {
                                     market = Some(Market::Fund);
@@ -125,6 +164,7 @@ impl Sina {
                                 name: v.get(4).unwrap().to_string(),
                                 market,
                                 exchange,
+                                sector: None,
                             })
                         }
                     }
@@ -138,32 +178,31 @@ impl Sina {
         }
     }
 
+    /// 公司资料 按标签解析 含首发信息及高管、董监事
     pub async fn profile(&self, symbol: &str) -> Result<Profile> {
         let corp_url =
             format!("https://vip.stock.finance.sina.com.cn/corp/go.php/vCI_CorpInfo/stockid/{}.phtml", &symbol[2..]);
         let info_url = format!("https://hq.sinajs.cn/list={},{}_i", symbol.to_lowercase(), symbol.to_lowercase());
-        let (corp, info) = join!(self.request(&corp_url), self.request(&info_url));
+        let ipo_url =
+            format!("https://vip.stock.finance.sina.com.cn/corp/go.php/vISSUE_NewStock/stockid/{}.phtml", &symbol[2..]);
+        let managers_url =
+            format!("https://vip.stock.finance.sina.com.cn/corp/go.php/vCI_CorpManager/stockid/{}.phtml", &symbol[2..]);
+        let (corp, info, ipo, managers) = join!(
+            self.request(&corp_url),
+            self.request(&info_url),
+            self.request(&ipo_url),
+            self.request(&managers_url)
+        );
 
-        let mut profile = Profile::default();
-        match corp {
-            Ok(content) => {
-                let doc = Html::parse_document(&content);
-                let tds = Selector::parse("#comInfo1 td").unwrap();
-                let a = Selector::parse("a").unwrap();
-                for (i, td) in doc.select(&tds).enumerate() {
-                    match i {
-                        1 => profile.name = td.inner_html().trim().to_string(),
-                        7 => profile.listing_date = td.select(&a).next().unwrap().inner_html().trim().to_string(),
-                        9 => profile.listing_price = td.inner_html().trim().parse::<f64>().unwrap_or(0.0),
-                        35 => profile.website = td.select(&a).next().unwrap().inner_html().trim().to_string(),
-                        41 => profile.used_name = td.inner_html().trim().to_string(),
-                        45 => profile.business_address = td.inner_html().trim().to_string(),
-                        49 => profile.business = td.inner_html().trim().to_string(),
-                        _ => {}
-                    }
-                }
-            }
-            Err(err) => error!("get corp failed, {}", err),
+        let mut profile = Profile { currency: Currency::from_symbol(symbol), ..Default::default() };
+        if let Err(err) = corp.and_then(|content| corp_from_str(&content, &mut profile)) {
+            error!("get corp failed, {}", err);
+        }
+        if let Err(err) = ipo.and_then(|content| ipo_from_str(&content, &mut profile)) {
+            error!("get ipo failed, {}", err);
+        }
+        if let Err(err) = managers.and_then(|content| managers_from_str(&content, &mut profile)) {
+            error!("get managers failed, {}", err);
         }
 
         match info {
@@ -185,16 +224,17 @@ impl Sina {
                             // 总股本
                             let cap = info.get(7).unwrap_or(&"").parse::<f64>().unwrap_or(0.0);
                             let traded_cap = info.get(8).unwrap_or(&"").parse::<f64>().unwrap_or(0.0);
+                            // 近四季净利润 亿元
                             let profit = info.get(18).unwrap_or(&"").parse::<f64>().unwrap_or(0.0);
 
-                            profile.pb = profile.price / vps;
-                            profile.pb = if profile.pb.is_nan() { 0.0 } else { profile.pb };
+                            profile.pb = ratio::pb(profile.price, vps);
                             profile.category = info.get(34).unwrap_or(&"").to_string();
+                            if let Ok(currency) = Currency::from_str(info.get(11).unwrap_or(&"")) {
+                                profile.currency = currency;
+                            }
                             profile.market_cap = profile.price * cap * 10000.0;
                             profile.traded_market_cap = profile.price * traded_cap * 10000.0;
-                            if profit > 0.0 {
-                                profile.pe_ttm = profile.market_cap / profit / 100000000.0
-                            }
+                            profile.pe_ttm = ratio::pe(profile.market_cap, profit * 100_000_000.0);
                         }
                         _ => {}
                     }
@@ -206,8 +246,10 @@ impl Sina {
         Ok(profile)
     }
 
-    // todo complete info
-    pub async fn financials(&self, code: &str) -> Result<Vec<Financial>> {
+    /// 财务摘要 报告期累计口径 取最近 limit 期 同比按日期匹配
+    ///
+    /// 每股及收益率指标来自财务指标页 按年请求 其他口径见 financials_by_period
+    pub async fn financials(&self, code: &str, limit: usize) -> Result<Vec<Financial>> {
         match self
             .request(&format!(
                 "https://money.finance.sina.com.cn/corp/go.php/vFD_FinanceSummary/stockid/{}.phtml",
@@ -216,127 +258,204 @@ impl Sina {
             .await
         {
             Ok(content) => {
-                let doc = Html::parse_document(&content);
-                let tds = Selector::parse("#FundHoldSharesTable tr td:last-child").unwrap();
-                let val = Selector::parse("strong,a").unwrap();
-                let mut results = Vec::new();
-                let mut financials = Vec::new();
-                let mut f = Financial::default();
-                let to_num = |s: &str| s.replace(',', "").replace('元', "").parse::<f64>().unwrap_or(0.0);
-                for (i, td) in doc.select(&tds).enumerate() {
-                    let val = match td.first_child() {
-                        Some(node) => match node.value() {
-                            Node::Text(txt) => txt.text.to_string(),
-                            Node::Element(_) => td.select(&val).next().unwrap().inner_html(),
-                            _ => "".to_string(),
-                        },
-                        None => "".to_string(),
-                    };
+                let mut financials = financials_from_str(&content, limit)?;
 
-                    match i {
-                        _ if i % 12 == 0 => {
-                            if i > 0 {
-                                financials.push(f);
-                                f = Financial::default();
-                            }
-                            f.date = val;
+                // 按年取财务指标
+                let mut years = financials.iter().map(|f| f.date.year()).collect::<Vec<_>>();
+                years.dedup();
+                let guides = join_all(years.iter().map(|year| {
+                    self.report(format!(
+                        "https://money.finance.sina.com.cn/corp/go.php/vFD_FinancialGuideLine/stockid/{}/ctrl/{}/displaytype/4.phtml",
+                        code, year
+                    ))
+                }))
+                .await;
+                for guide in guides.iter() {
+                    let guide = match guide {
+                        Ok(guide) => guide,
+                        Err(err) => {
+                            error!("get financial guide failed, {}", err);
+                            continue;
                         }
-                        _ if i % 12 == 1 => f.ps_net_assets = to_num(&val),
-                        _ if i % 12 == 3 => f.ps_capital_reserve = to_num(&val),
-                        _ if i % 12 == 8 => f.total_revenue = to_num(&val),
-                        _ if i % 12 == 10 => f.net_profit = to_num(&val),
-                        _ if i > 95 => break, // 取最近8季度
-                        _ => {}
-                    }
-                }
-
-                for i in 0..4 {
-                    if let Some(cur) = financials.get(i) {
-                        let mut f = cur.clone();
-                        if let Some(prev) = financials.get(i + 4) {
-                            f.total_revenue_rate = (f.total_revenue - prev.total_revenue) / prev.total_revenue * 100.0;
-                            f.net_profit_rate = (f.net_profit - prev.net_profit) / prev.net_profit * 100.0;
+                    };
+                    for f in financials.iter_mut() {
+                        if let Some(i) = guide.index(f.date) {
+                            if f.eps == 0.0 {
+                                f.eps = guide.get(i, &["摊薄每股收益", "加权每股收益"]);
+                            }
+                            f.eps_undistributed = guide.get(i, &["每股未分配利润"]);
+                            f.ps_cash_flow = guide.get(i, &["每股经营性现金流"]);
+                            f.roe = guide.get(i, &["净资产收益率", "加权净资产收益率"]);
+                            f.net_profit_after_nrgal = guide.get(i, &["扣除非经常性损益后的净利润"]);
                         }
-                        results.push(f);
                     }
                 }
 
-                Ok(results)
+                Ok(financials_by_period(&financials, Period::Cumulative, limit))
             }
             Err(err) => bail!("get financials failed, {}", err),
         }
     }
 
+    /// 资产负债表 最近 limit 期
+    pub async fn balance_sheets(&self, code: &str, limit: usize) -> Result<Vec<BalanceSheet>> {
+        let report = self.statements("vFD_BalanceSheet", code, limit).await?;
+        Ok((0..report.dates.len())
+            .map(|i| BalanceSheet {
+                date: report.dates[i],
+                cash: report.get(i, &["货币资金"]) * 10000.0,
+                accounts_receivable: report.get(i, &["应收账款"]) * 10000.0,
+                inventory: report.get(i, &["存货"]) * 10000.0,
+                current_assets: report.get(i, &["流动资产合计"]) * 10000.0,
+                fixed_assets: report.get(i, &["固定资产及清理合计", "固定资产净额", "固定资产"]) * 10000.0,
+                total_assets: report.get(i, &["资产总计"]) * 10000.0,
+                short_term_loans: report.get(i, &["短期借款"]) * 10000.0,
+                accounts_payable: report.get(i, &["应付账款"]) * 10000.0,
+                current_liabilities: report.get(i, &["流动负债合计"]) * 10000.0,
+                long_term_loans: report.get(i, &["长期借款"]) * 10000.0,
+                total_liabilities: report.get(i, &["负债合计"]) * 10000.0,
+                share_capital: report.get(i, &["实收资本"]) * 10000.0,
+                capital_reserve: report.get(i, &["资本公积"]) * 10000.0,
+                undistributed_profit: report.get(i, &["未分配利润"]) * 10000.0,
+                parent_equity: report.get(i, &["归属于母公司股东权益合计", "归属于母公司所有者权益合计"]) * 10000.0,
+                total_equity: report.get(i, &["所有者权益(或股东权益)合计", "所有者权益合计", "股东权益合计"])
+                    * 10000.0,
+            })
+            .collect())
+    }
+
+    /// 利润表 最近 limit 期 累计值
+    pub async fn income_statements(&self, code: &str, limit: usize) -> Result<Vec<IncomeStatement>> {
+        let report = self.statements("vFD_ProfitStatement", code, limit).await?;
+        Ok((0..report.dates.len())
+            .map(|i| IncomeStatement {
+                date: report.dates[i],
+                total_revenue: report.get(i, &["营业总收入"]) * 10000.0,
+                revenue: report.get(i, &["营业收入"]) * 10000.0,
+                total_cost: report.get(i, &["营业总成本"]) * 10000.0,
+                cost: report.get(i, &["营业成本"]) * 10000.0,
+                selling_expenses: report.get(i, &["销售费用"]) * 10000.0,
+                admin_expenses: report.get(i, &["管理费用"]) * 10000.0,
+                rd_expenses: report.get(i, &["研发费用"]) * 10000.0,
+                financial_expenses: report.get(i, &["财务费用"]) * 10000.0,
+                operating_profit: report.get(i, &["营业利润"]) * 10000.0,
+                total_profit: report.get(i, &["利润总额"]) * 10000.0,
+                income_tax: report.get(i, &["所得税费用"]) * 10000.0,
+                net_profit: report.get(i, &["净利润"]) * 10000.0,
+                parent_net_profit: report.get(i, &["归属于母公司所有者的净利润", "归属于母公司的净利润"]) * 10000.0,
+                basic_eps: report.get(i, &["基本每股收益"]),
+                diluted_eps: report.get(i, &["稀释每股收益"]),
+            })
+            .collect())
+    }
+
+    /// 现金流量表 最近 limit 期 累计值
+    pub async fn cash_flows(&self, code: &str, limit: usize) -> Result<Vec<CashFlow>> {
+        let report = self.statements("vFD_CashFlow", code, limit).await?;
+        Ok((0..report.dates.len())
+            .map(|i| CashFlow {
+                date: report.dates[i],
+                operating: report.get(i, &["经营活动产生的现金流量净额"]) * 10000.0,
+                investing: report.get(i, &["投资活动产生的现金流量净额"]) * 10000.0,
+                financing: report.get(i, &["筹资活动产生的现金流量净额"]) * 10000.0,
+                capex: report.get(i, &["购建固定资产、无形资产和其他长期资产所支付的现金"]) * 10000.0,
+                net_increase: report.get(i, &["现金及现金等价物净增加额"]) * 10000.0,
+                ending_cash: report.get(i, &["期末现金及现金等价物余额"]) * 10000.0,
+            })
+            .collect())
+    }
+
+    /// 默认页仅有最近 5 期 不足 limit 期时逐年补齐更早的 按日期合并
+    async fn statements(&self, kind: &str, code: &str, limit: usize) -> Result<Report> {
+        let url = |ctrl: &str| {
+            format!(
+                "https://money.finance.sina.com.cn/corp/go.php/{}/stockid/{}/ctrl/{}/displaytype/4.phtml",
+                kind, code, ctrl
+            )
+        };
+        let latest = self.report(url("part")).await?;
+        let url = &url;
+        Ok(Report::backfill(latest, limit, |year| async move {
+            match self.report(url(&year.to_string())).await {
+                Ok(report) => Some(report),
+                Err(err) => {
+                    error!("get {} {} report failed, {}", kind, year, err);
+                    None
+                }
+            }
+        })
+        .await)
+    }
+
+    async fn report(&self, url: String) -> Result<Report> {
+        match self.request(&url).await {
+            Ok(content) => Report::parse(&content),
+            Err(err) => bail!("get report failed, {}", err),
+        }
+    }
+
+    /// 股东结构 含十大股东、十大流通股东及基金持股 流通股东及基金按截止日期合并
     pub async fn structures(&self, code: &str) -> Result<Vec<Structure>> {
+        let url =
+            |page: &str| format!("https://vip.stock.finance.sina.com.cn/corp/go.php/{}/stockid/{}.phtml", page, code);
+        let (holders_url, floats_url, funds_url) =
+            (url("vCI_StockHolder"), url("vCI_CirculateStockHolder"), url("vCI_FundStockHolder"));
+        let (holders, floats, funds) =
+            join!(self.request(&holders_url), self.request(&floats_url), self.request(&funds_url));
+
+        let mut structures = match holders {
+            Ok(content) => structures_from_str(&content)?,
+            Err(err) => bail!("get structures failed, {}", err),
+        };
+        match floats.and_then(|content| floats_from_str(&content)) {
+            Ok(floats) => {
+                for (date, holders) in floats {
+                    if let Some(s) = structures.iter_mut().find(|s| s.date == date) {
+                        s.holders_float = holders;
+                    }
+                }
+            }
+            Err(err) => error!("get float holders failed, {}", err),
+        }
+        match funds.and_then(|content| funds_from_str(&content)) {
+            Ok(funds) => {
+                for (date, funds) in funds {
+                    if let Some(s) = structures.iter_mut().find(|s| s.date == date) {
+                        s.funds = funds;
+                    }
+                }
+            }
+            Err(err) => error!("get fund holdings failed, {}", err),
+        }
+
+        Ok(structures)
+    }
+
+    /// 股本结构 历次变动 按日期倒序
+    pub async fn capitals(&self, code: &str) -> Result<Vec<Capital>> {
         match self
             .request(&format!(
-                "https://vip.stock.finance.sina.com.cn/corp/go.php/vCI_StockHolder/stockid/{}.phtml",
+                "https://vip.stock.finance.sina.com.cn/corp/go.php/vCI_StockStructure/stockid/{}.phtml",
                 code
             ))
             .await
         {
-            Ok(content) => {
-                let doc = Html::parse_document(&content);
-                let trs = Selector::parse("#Table1 tbody tr").unwrap();
-                let td1 = Selector::parse("td:last-child").unwrap();
-                let td2 = Selector::parse("td div").unwrap();
-                let mut structures = Vec::new();
-                let mut s = Structure::default();
-                let get_link_val = |er: ElementRef| -> String {
-                    let a = Selector::parse("a").unwrap();
-                    match er.first_child().unwrap().value() {
-                        Node::Text(txt) => txt.text.to_string(),
-                        Node::Element(_) => er.select(&a).next().unwrap().inner_html(),
-                        _ => "".to_string(),
-                    }
-                };
-                for (i, tr) in doc.select(&trs).enumerate() {
-                    let val = tr.select(&td1).next().unwrap().inner_html();
-
-                    match i {
-                        _ if i % 17 == 0 => {
-                            if i > 0 {
-                                structures.push(s);
-                                s = Structure::default();
-                            }
-                            s.date = val;
-                        }
-                        _ if i % 17 == 3 => s.holders_num = num_from_str(&val),
-                        _ if i % 17 == 4 => s.shares_avg = num_from_str(&val),
-                        _ if i % 17 == 6
-                            || i % 17 == 7
-                            || i % 17 == 8
-                            || i % 17 == 9
-                            || i % 17 == 10
-                            || i % 17 == 11
-                            || i % 17 == 12
-                            || i % 17 == 13
-                            || i % 17 == 14
-                            || i % 17 == 15 =>
-                        {
-                            let mut h = Holder::default();
-                            for (m, td) in tr.select(&td2).enumerate() {
-                                match m {
-                                    1 => h.name = get_link_val(td),
-                                    2 => h.shares = get_link_val(td).parse::<f64>().unwrap_or(0.0),
-                                    3 => h.percent = get_link_val(td).parse::<f64>().unwrap_or(0.0),
-                                    4 => h.shares_type = get_link_val(td),
-                                    _ => {}
-                                }
-                            }
-                            s.holders_ten.push(h);
-                        }
-                        _ => {}
-                    }
-
-                    if i > 68 {
-                        break;
-                    }
-                }
+            Ok(content) => capitals_from_str(&content),
+            Err(err) => bail!("get capitals failed, {}", err),
+        }
+    }
 
-                Ok(structures)
-            }
-            Err(err) => bail!("get presses failed, {}", err),
+    /// 限售解禁 含已解禁及待解禁 symbol: SH601318
+    pub async fn unlocks(&self, symbol: &str) -> Result<Vec<Unlock>> {
+        match self
+            .request(&format!(
+                "https://vip.stock.finance.sina.com.cn/q/go.php/vInvestConsult/kind/xsjj/index.phtml?symbol={}",
+                symbol.to_lowercase()
+            ))
+            .await
+        {
+            Ok(content) => unlocks_from_str(&content),
+            Err(err) => bail!("get unlocks failed, {}", err),
         }
     }
 
@@ -348,41 +467,22 @@ impl Sina {
             ))
             .await
         {
-            Ok(content) => {
-                let doc = Html::parse_document(&content);
-                let tds = Selector::parse("#sharebonus_1 tr td").unwrap();
-                let mut dividends = Vec::new();
-                let mut d = Dividend::default();
-                for (i, td) in doc.select(&tds).enumerate() {
-                    let val = td.inner_html();
-                    match i {
-                        _ if i % 9 == 0 => {
-                            if i > 0 {
-                                dividends.push(d);
-                                d = Dividend::default();
-                            }
-                            d.date = val;
-                        }
-                        _ if i % 9 == 1 => d.shares_dividend = val.parse::<f64>().unwrap_or(0.0),
-                        _ if i % 9 == 2 => d.shares_into = val.parse::<f64>().unwrap_or(0.0),
-                        _ if i % 9 == 3 => d.money = val.parse::<f64>().unwrap_or(0.0),
-                        _ if i % 9 == 5 => d.date_dividend = val,
-                        _ if i % 9 == 6 => d.date_record = val,
-                        _ => {}
-                    }
-                }
-
-                Ok(dividends)
-            }
+            Ok(content) => dividends_from_str(&content),
             Err(err) => bail!("get dividends failed, {}", err),
         }
     }
 
+    /// 最新公告 第一页
     pub async fn presses(&self, code: &str) -> Result<Vec<Press>> {
+        self.presses_page(code, 1).await
+    }
+
+    /// 公告列表 page 从 1 开始 按日期降序 超出页数时为空
+    pub async fn presses_page(&self, code: &str, page: usize) -> Result<Vec<Press>> {
         match self
             .request(&format!(
-                "https://vip.stock.finance.sina.com.cn/corp/go.php/vCB_AllBulletin/stockid/{}.phtml",
-                code
+                "https://vip.stock.finance.sina.com.cn/corp/go.php/vCB_AllBulletin/stockid/{}.phtml?Page={}",
+                code, page
             ))
             .await
         {
@@ -390,25 +490,24 @@ impl Sina {
                 let doc = Html::parse_document(&content);
                 let ul = Selector::parse("div.datelist ul").unwrap();
                 let mut presses = Vec::new();
-                let mut p = Press::default();
-                if let Some(ele) = doc.select(&ul).next() {
-                    for (i, item) in ele.children().enumerate() {
-                        match i {
-                            _ if i % 3 == 0 => {
-                                if i > 0 {
-                                    presses.push(p);
-                                    p = Press::default();
-                                }
-                                p.date = item.value().as_text().unwrap().trim().to_string();
-                            }
-                            _ if i % 3 == 1 => {
-                                let ele = item.value().as_element().unwrap();
-                                p.url = format!("https://vip.stock.finance.sina.com.cn/{}", ele.attr("href").unwrap());
-                                let txt = item.children().next().unwrap().value().as_text().unwrap();
-                                p.title = txt.text.to_string();
+                // 日期文本后跟公告链接 eg: 2022-03-29&nbsp;<a href="...">title</a><br>
+                let mut date = None;
+                for item in doc.select(&ul).next().iter().flat_map(|ele| ele.children()) {
+                    match item.value() {
+                        Node::Text(txt) => date = parse_date(txt.trim()).or(date),
+                        Node::Element(ele) if ele.name() == "a" => {
+                            if let (Some(date), Some(href)) = (date, ele.attr("href")) {
+                                presses.push(Press {
+                                    date,
+                                    title: ElementRef::wrap(item)
+                                        .map(|a| a.text().collect::<String>().trim().to_string())
+                                        .unwrap_or_default(),
+                                    url: format!("https://vip.stock.finance.sina.com.cn/{}", href),
+                                    ..Default::default()
+                                });
                             }
-                            _ => {}
                         }
+                        _ => {}
                     }
                 }
 
@@ -418,16 +517,250 @@ impl Sina {
         }
     }
 
+    /// 公告详情 返回 (PDF 附件地址, 正文纯文本) 无附件时地址为空
+    pub async fn press_detail(&self, url: &str) -> Result<(String, String)> {
+        match self.request(url).await {
+            Ok(content) => {
+                let doc = Html::parse_document(&content);
+                let a = Selector::parse("a[href]").unwrap();
+                // 相对链接按公告页地址补全
+                let file = doc
+                    .select(&a)
+                    .filter_map(|a| a.value().attr("href"))
+                    .find(|href| href.to_lowercase().ends_with(".pdf"))
+                    .and_then(|href| Url::parse(url).and_then(|base| base.join(href)).ok())
+                    .map_or(String::new(), |file| file.to_string());
+                let body = Selector::parse("#content").unwrap();
+                let text = doc
+                    .select(&body)
+                    .next()
+                    .map(|body| body.text().map(|t| t.trim()).filter(|t| !t.is_empty()).collect::<Vec<_>>().join("\n"))
+                    .unwrap_or_default();
+
+                Ok((file, text))
+            }
+            Err(err) => bail!("get press detail failed, {}", err),
+        }
+    }
+
+    /// 下载文件 返回字节数
+    pub async fn download(&self, url: &str, path: &Path) -> Result<usize> {
+        match self.client.get(url).send().await {
+            Ok(resp) if resp.status() == StatusCode::OK => {
+                let bytes = resp.bytes().await?;
+                fs::write(path, &bytes)?;
+                Ok(bytes.len())
+            }
+            Ok(resp) => bail!("download {} failed, http code: {}", url, resp.status()),
+            Err(err) => bail!("download {} failed, {}", url, err),
+        }
+    }
+
     /// symbols: sz000001,sh601318
+    ///
+    /// 超过 QUOTES_BATCH 个时分批并发请求
     pub async fn quotes(&self, symbols: &str) -> Result<Vec<Quote>> {
-        match self.request(&format!("https://hq.sinajs.cn/list={}", fmt_quote_symbols(symbols))).await {
+        let content = self.quotes_raw(symbols).await?;
+        Ok(quotes_from_str(HTTP_QUOTES_PATTERN, &content))
+    }
+
+    /// 行情接口原始返回 多批时以换行拼接
+    pub async fn quotes_raw(&self, symbols: &str) -> Result<String> {
+        let symbols = split_symbols(symbols);
+        let batches = symbols
+            .chunks(QUOTES_BATCH)
+            .map(|chunk| format!("https://hq.sinajs.cn/list={}", fmt_quote_symbols(&chunk.join(","))))
+            .collect::<Vec<String>>();
+        let mut contents = Vec::new();
+        for result in join_all(batches.iter().map(|url| self.request(url))).await {
+            match result {
+                Ok(content) => {
+                    debug!("quotes result: {}", content);
+                    contents.push(content);
+                }
+                Err(err) => bail!(err),
+            }
+        }
+
+        Ok(contents.join("\n"))
+    }
+
+    /// 港元 美元兑人民币汇率 取在岸人民币最新价
+    pub async fn rates(&self) -> Result<Rates> {
+        match self.request("https://hq.sinajs.cn/list=fx_shkdcny,fx_susdcny").await {
             Ok(content) => {
-                debug!("quotes result: {}", content);
-                let quotes = quotes_from_str("hq_str_(?:rt_)?(?:gb_)?([A-Za-z0-9]+)=\"(.*)\"", &content);
-                Ok(quotes)
+                debug!("rates result: {}", content);
+                let rates = rates_from_str(&content);
+                if rates.hkd <= 0.0 || rates.usd <= 0.0 {
+                    bail!("get rates failed, {}", content);
+                }
+                Ok(rates)
+            }
+            Err(err) => bail!("get rates failed, {}", err),
+        }
+    }
+
+    /// 日K线 仅支持沪深北证 按日期升序 最多 1023 条
+    ///
+    /// symbols: sh601318
+    pub async fn bars(&self, symbol: &str, len: usize) -> Result<Vec<Bar>> {
+        match self
+            .request(&format!(
+                "https://quotes.sina.cn/cn/api/json_v2.php/CN_MarketDataService.getKLineData?symbol={}&scale=240&ma=no&datalen={}",
+                symbol.to_lowercase(),
+                len
+            ))
+            .await
+        {
+            Ok(content) => {
+                debug!("bars result: {}", content);
+                let values = match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(serde_json::Value::Array(values)) => values,
+                    Ok(_) => return Ok(Vec::new()),
+                    Err(err) => bail!("parse bars failed, {}", err),
+                };
+                let to_num = |v: &serde_json::Value| v.as_str().unwrap_or("").parse::<f64>().unwrap_or(0.0);
+                // 日期无法解析的跳过
+                let bars = values
+                    .iter()
+                    .filter_map(|v| {
+                        Some(Bar {
+                            date: parse_date(v["day"].as_str().unwrap_or(""))?,
+                            open: to_num(&v["open"]),
+                            high: to_num(&v["high"]),
+                            low: to_num(&v["low"]),
+                            close: to_num(&v["close"]),
+                            turnover: to_num(&v["volume"]),
+                        })
+                    })
+                    .collect();
+                Ok(bars)
+            }
+            Err(err) => bail!("get bars failed, {}", err),
+        }
+    }
+
+    /// 沪深京 A 股全市场行情快照
+    pub async fn snapshot(&self) -> Result<Vec<Snapshot>> {
+        // hs_a 沪深 A 股 hs_bjs 北证
+        self.node_snapshots(&["hs_a", "hs_bjs"]).await
+    }
+
+    /// 板块成分股行情快照
+    pub async fn sector_members(&self, sector: &Sector) -> Result<Vec<Snapshot>> {
+        self.node_snapshots(&[&sector.code]).await
+    }
+
+    /// 申万一二三级行业及概念板块的成分股代码 用于得到个股所属板块 需逐个获取成分股
+    ///
+    /// 单个板块获取失败时跳过
+    pub async fn sector_symbols(&self) -> Result<Vec<(Sector, Vec<String>)>> {
+        let sectors = self.sectors().await?.into_iter().filter(|s| s.kind != SectorKind::Industry).collect::<Vec<_>>();
+        let mut results = Vec::new();
+        for chunk in sectors.chunks(SNAPSHOT_CONCURRENCY) {
+            for (sector, members) in chunk.iter().zip(join_all(chunk.iter().map(|s| self.sector_members(s))).await) {
+                match members {
+                    Ok(members) => results.push((sector.clone(), members.into_iter().map(|s| s.symbol).collect())),
+                    Err(err) => error!("get {} members failed, {}", sector.name, err),
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// 行情中心板块列表 含新浪行业 申万一二三级行业及概念板块
+    pub async fn sectors(&self) -> Result<Vec<Sector>> {
+        let content = self.request(&format!("{}.getHQNodes", MARKET_CENTER)).await?;
+        let nodes = match serde_json::from_str::<serde_json::Value>(&content) {
+            Ok(nodes) => nodes,
+            Err(err) => bail!("parse sectors failed, {}", err),
+        };
+
+        let mut sectors = Vec::new();
+        sectors_from_nodes(&nodes, &mut sectors);
+        Ok(sectors)
+    }
+
+    /// 港股全市场行情快照
+    pub async fn hk_snapshot(&self) -> Result<Vec<Snapshot>> {
+        let count = self.request(&format!("{}.getHKStockCount?node=qbgg_hk", MARKET_CENTER)).await?;
+        let count = match count.trim().trim_matches('"').parse::<usize>() {
+            Ok(count) => count,
+            Err(_) => bail!("get hk count failed, {}", count),
+        };
+        let urls = (1..=count.div_ceil(SNAPSHOT_PAGE))
+            .map(|page| {
+                format!(
+                    "{}.getHKStockData?page={}&num={}&sort=symbol&asc=1&node=qbgg_hk",
+                    MARKET_CENTER, page, SNAPSHOT_PAGE
+                )
+            })
+            .collect::<Vec<_>>();
+        self.paged_snapshots(&urls, |content| match serde_json::from_str::<serde_json::Value>(content) {
+            Ok(serde_json::Value::Array(values)) => Ok(values.iter().map(snapshot_from_json_hk).collect()),
+            Ok(_) => Ok(Vec::new()),
+            Err(err) => bail!("parse hk snapshot failed, {}", err),
+        })
+        .await
+    }
+
+    /// 美股全市场行情快照 首页返回总数
+    pub async fn us_snapshot(&self) -> Result<Vec<Snapshot>> {
+        let url = |page: usize| {
+            format!(
+                "https://stock.finance.sina.com.cn/usstock/api/jsonp.php/IO/US_CategoryService.getList?page={}&num={}&sort=symbol&asc=1",
+                page, SNAPSHOT_PAGE
+            )
+        };
+        let (count, mut snapshots) = us_list_from_str(&self.request(&url(1)).await?)?;
+        let urls = (2..=count.div_ceil(SNAPSHOT_PAGE)).map(url).collect::<Vec<_>>();
+        snapshots.extend(self.paged_snapshots(&urls, |content| Ok(us_list_from_str(content)?.1)).await?);
+        Ok(snapshots)
+    }
+
+    /// 按行情中心列表分页并发获取
+    async fn node_snapshots(&self, nodes: &[&str]) -> Result<Vec<Snapshot>> {
+        let mut urls = Vec::new();
+        for node in nodes.iter() {
+            let count = self.request(&format!("{}.getHQNodeStockCount?node={}", MARKET_CENTER, node)).await?;
+            let count = match count.trim().trim_matches('"').parse::<usize>() {
+                Ok(count) => count,
+                Err(_) => bail!("get {} count failed, {}", node, count),
+            };
+            for page in 1..=count.div_ceil(SNAPSHOT_PAGE) {
+                urls.push(format!(
+                    "{}.getHQNodeData?page={}&num={}&sort=symbol&asc=1&node={}",
+                    MARKET_CENTER, page, SNAPSHOT_PAGE, node
+                ));
+            }
+        }
+
+        self.paged_snapshots(&urls, |content| match serde_json::from_str::<serde_json::Value>(content) {
+            Ok(serde_json::Value::Array(values)) => Ok(values.iter().map(snapshot_from_json).collect()),
+            Ok(_) => Ok(Vec::new()),
+            Err(err) => bail!("parse snapshot failed, {}", err),
+        })
+        .await
+    }
+
+    /// 按 SNAPSHOT_CONCURRENCY 分批并发请求各页
+    async fn paged_snapshots(
+        &self,
+        urls: &[String],
+        parse: fn(&str) -> Result<Vec<Snapshot>>,
+    ) -> Result<Vec<Snapshot>> {
+        let mut snapshots = Vec::new();
+        for chunk in urls.chunks(SNAPSHOT_CONCURRENCY) {
+            for result in join_all(chunk.iter().map(|url| self.request(url))).await {
+                match result {
+                    Ok(content) => snapshots.extend(parse(&content)?),
+                    Err(err) => bail!("get snapshot failed, {}", err),
+                }
             }
-            Err(err) => bail!(err),
         }
+
+        Ok(snapshots)
     }
 
     async fn request(&self, url: &str) -> Result<String> {
@@ -445,39 +778,421 @@ impl Sina {
         }
     }
 
+    /// 实时行情 断线按指数退避自动重连 连续失败时降级为 HTTP 轮询
+    ///
     /// 多个时连接时返回所有 之后单个返回
-    pub async fn quotes_ws(symbols: &str, handler: impl Fn(Vec<Quote>)) {
+    pub async fn quotes_ws(&self, symbols: &str, handler: impl Fn(QuoteEvent)) -> Result<()> {
+        self.quotes_ws_with(symbols, &WsOptions::default(), handler).await
+    }
+
+    pub async fn quotes_ws_with(&self, symbols: &str, opts: &WsOptions, handler: impl Fn(QuoteEvent)) -> Result<()> {
+        let mut stream = self.quote_stream_with(symbols, opts)?;
+        while let Some(event) = stream.next().await {
+            handler(event);
+        }
+
+        Ok(())
+    }
+
+    /// 订阅实时行情 需在 tokio 运行时中调用
+    pub fn quote_stream(&self, symbols: &str) -> Result<QuoteStream> {
+        self.quote_stream_with(symbols, &WsOptions::default())
+    }
+
+    /// 设置了 opts.record 但无法打开时返回错误
+    pub fn quote_stream_with(&self, symbols: &str, opts: &WsOptions) -> Result<QuoteStream> {
+        self.subscribe(symbols, opts, |subscriber| Box::pin(subscriber.run()))
+    }
+
+    /// 以 HTTP 按 opts.poll_interval 轮询订阅实时行情 首次返回全部 之后仅返回变化的
+    pub fn quote_poll_stream(&self, symbols: &str, opts: &WsOptions) -> Result<QuoteStream> {
+        self.subscribe(symbols, opts, |mut subscriber| Box::pin(async move { subscriber.poll(None).await }))
+    }
+
+    /// 回放 WsOptions::record 录制的文件 speed 为倍速 结束后 Stream 返回 None
+    pub fn replay(&self, path: impl AsRef<Path>, speed: f64) -> Result<QuoteStream> {
+        if !(speed.is_finite() && speed > 0.0) {
+            bail!("回放倍速需大于 0");
+        }
+
+        let content = fs::read_to_string(path)?;
+        let mut frames = Vec::new();
+        for (i, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            match serde_json::from_str::<Frame>(line) {
+                Ok(frame) => frames.push(frame),
+                Err(err) => bail!("第 {} 行格式错误，{}", i + 1, err),
+            }
+        }
+
+        self.subscribe("", &WsOptions::default(), |subscriber| Box::pin(subscriber.replay(frames, speed)))
+    }
+
+    fn subscribe(
+        &self,
+        symbols: &str,
+        opts: &WsOptions,
+        run: impl FnOnce(Subscriber) -> Pin<Box<dyn Future<Output = ()> + Send>>,
+    ) -> Result<QuoteStream> {
+        let recorder = match opts.record.as_ref() {
+            Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => Some(file),
+                Err(err) => bail!("open record file {} failed, {}", path.display(), err),
+            },
+            None => None,
+        };
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let (commands, commands_rx) = mpsc::unbounded_channel();
+        let mut subscriber = Subscriber {
+            sina: self.clone(),
+            symbols: Vec::new(),
+            opts: opts.clone(),
+            snapshot: HashMap::new(),
+            recorder,
+            events: events_tx,
+            commands: commands_rx,
+        };
+        subscriber.apply(Command::Add(split_symbols(symbols)));
+        let task = tokio::spawn(run(subscriber));
+
+        Ok(QuoteStream { events, commands, task })
+    }
+}
+
+/// 实时行情订阅 按 Stream 产出事件
+///
+/// 可随时增删代码 drop 或 close 后断开连接
+#[derive(Debug)]
+pub struct QuoteStream {
+    events: mpsc::UnboundedReceiver<QuoteEvent>,
+    commands: mpsc::UnboundedSender<Command>,
+    task: JoinHandle<()>,
+}
+
+impl QuoteStream {
+    /// symbols: sz000001,sh601318
+    pub fn add(&self, symbols: &str) {
+        let _ = self.commands.send(Command::Add(split_symbols(symbols)));
+    }
+
+    pub fn remove(&self, symbols: &str) {
+        let _ = self.commands.send(Command::Remove(split_symbols(symbols)));
+    }
+
+    pub fn close(&self) {
+        self.task.abort();
+    }
+}
+
+impl Stream for QuoteStream {
+    type Item = QuoteEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for QuoteStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Debug)]
+enum Command {
+    Add(Vec<String>),
+    Remove(Vec<String>),
+}
+
+/// 录制的原始行情帧 每行一条 JSON
+#[derive(Debug, Serialize, Deserialize)]
+struct Frame {
+    /// 接收时间 毫秒时间戳
+    ts: u64,
+    source: FrameSource,
+    raw: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FrameSource {
+    Ws,
+    Http,
+}
+
+/// 订阅后台任务 维护连接及代码列表
+struct Subscriber {
+    sina: Sina,
+    symbols: Vec<String>,
+    opts: WsOptions,
+    /// 最近一次行情 用于轮询时比对变化
+    snapshot: HashMap<String, Quote>,
+    /// 原始行情帧录制
+    recorder: Option<File>,
+    events: mpsc::UnboundedSender<QuoteEvent>,
+    commands: mpsc::UnboundedReceiver<Command>,
+}
+
+impl Subscriber {
+    async fn run(mut self) {
+        let mut failures = 0;
+        loop {
+            if self.symbols.is_empty() {
+                match self.commands.recv().await {
+                    Some(cmd) => self.apply(cmd),
+                    None => return,
+                };
+                continue;
+            }
+
+            match self.session().await {
+                // 代码变更 立即以新列表重连
+                Ok(None) => {
+                    failures = 0;
+                    continue;
+                }
+                Ok(Some(reason)) => {
+                    failures = 0;
+                    self.emit(QuoteEvent::Disconnected(reason));
+                }
+                Err(err) => {
+                    failures += 1;
+                    self.emit(QuoteEvent::Disconnected(err.to_string()));
+                }
+            }
+            if self.events.is_closed() {
+                return;
+            }
+
+            if failures >= self.opts.poll_after_failures {
+                self.emit(QuoteEvent::Polling);
+                self.poll(Some(Instant::now() + self.opts.backoff_max)).await;
+                continue;
+            }
+
+            let delay = self.opts.backoff(failures);
+            self.emit(QuoteEvent::Reconnecting { attempt: failures, delay });
+            let wait = sleep(delay);
+            tokio::pin!(wait);
+            loop {
+                select! {
+                    _ = &mut wait => break,
+                    cmd = self.commands.recv() => match cmd {
+                        Some(cmd) => {
+                            self.apply(cmd);
+                        }
+                        None => return,
+                    },
+                }
+            }
+        }
+    }
+
+    /// 新浪 wskt 仅支持在连接地址中订阅 代码变更时结束本次连接
+    ///
+    /// 连接失败返回错误 代码变更返回 None 其他原因断开返回原因
+    async fn session(&mut self) -> Result<Option<String>> {
         let req = Request::builder()
             .method(Method::GET)
-            .uri(format!("wss://hq.sinajs.cn/wskt?list={}", fmt_quote_symbols(symbols)))
+            .uri(format!("wss://hq.sinajs.cn/wskt?list={}", fmt_quote_symbols(&self.symbols.join(","))))
             .header(header::ORIGIN, HeaderValue::from_static(PORTAL))
             .body(())
             .unwrap();
 
-        let (ws, _) = connect_async(req).await.unwrap();
+        let ws = match connect_async(req).await {
+            Ok((ws, _)) => ws,
+            Err(err) => bail!("ws connect failed, {}", err),
+        };
+        self.emit(QuoteEvent::Connected);
+
         let (mut sender, mut receiver) = ws.split();
         let mut interval = interval(Duration::from_secs(60));
+        let stale = sleep(self.opts.stale_timeout);
+        tokio::pin!(stale);
 
         loop {
             select! {
-                msg = receiver.next() => {
-                    if let Some(msg) = msg {
-                        let msg = msg.unwrap();
+                msg = receiver.next() => match msg {
+                    Some(Ok(msg)) => {
+                        stale.as_mut().reset(Instant::now() + self.opts.stale_timeout);
                         if msg.is_text() {
                             debug!("ws receive msg: {}", msg);
-                            let quotes = quotes_from_str("(?:rt_)?(?:gb_)?([A-Za-z0-9]+)=(.*)\\n", &msg.to_string());
-                            handler(quotes);
+                            let raw = msg.to_string();
+                            self.record(FrameSource::Ws, &raw);
+                            self.emit_quotes(quotes_from_str(WS_QUOTES_PATTERN, &raw), false);
+                        } else if msg.is_close() {
+                            return Ok(Some("ws closed by server".to_string()));
                         }
                     }
+                    Some(Err(err)) => return Ok(Some(format!("ws receive failed, {}", err))),
+                    None => return Ok(Some("ws stream ended".to_string())),
+                },
+                cmd = self.commands.recv() => match cmd {
+                    Some(cmd) => {
+                        if self.apply(cmd) {
+                            let _ = sender.close().await;
+                            return Ok(None);
+                        }
+                    }
+                    None => return Ok(Some("subscription closed".to_string())),
+                },
+                _ = interval.tick() => {
+                    if let Err(err) = sender.send(Message::Text("".to_string())).await {
+                        return Ok(Some(format!("ws heartbeat failed, {}", err)));
+                    }
                 }
+                _ = &mut stale => {
+                    // 休市时无推送属正常 不重连
+                    if !self.in_session() {
+                        stale.as_mut().reset(Instant::now() + self.opts.stale_timeout);
+                        continue;
+                    }
+                    self.emit(QuoteEvent::Stale(self.opts.stale_timeout));
+                    return Ok(Some("ws stale".to_string()));
+                }
+            }
+        }
+    }
+
+    /// 按 poll_interval 轮询至 deadline 为空时不停止 仅返回有变化的行情
+    async fn poll(&mut self, deadline: Option<Instant>) {
+        let mut interval = interval(self.opts.poll_interval);
+        while deadline.is_none_or(|deadline| Instant::now() < deadline) {
+            select! {
                 _ = interval.tick() => {
-                    sender.send(Message::Text("".to_string())).await.unwrap();
+                    if self.symbols.is_empty() {
+                        continue;
+                    }
+                    match self.sina.quotes_raw(&self.symbols.join(",")).await {
+                        Ok(raw) => {
+                            self.record(FrameSource::Http, &raw);
+                            self.emit_quotes(quotes_from_str(HTTP_QUOTES_PATTERN, &raw), true);
+                        }
+                        Err(err) => debug!("poll quotes failed, {}", err),
+                    }
                 }
+                cmd = self.commands.recv() => match cmd {
+                    Some(cmd) => {
+                        self.apply(cmd);
+                    }
+                    None => return,
+                },
+            }
+        }
+    }
+
+    /// 返回代码列表是否变更
+    fn apply(&mut self, cmd: Command) -> bool {
+        let before = self.symbols.len();
+        match cmd {
+            Command::Add(symbols) => {
+                for symbol in symbols {
+                    if !self.symbols.iter().any(|s| s.eq_ignore_ascii_case(&symbol)) {
+                        self.symbols.push(symbol);
+                    }
+                }
+            }
+            Command::Remove(symbols) => {
+                self.symbols.retain(|s| !symbols.iter().any(|symbol| s.eq_ignore_ascii_case(symbol)));
+            }
+        }
+
+        self.symbols.len() != before
+    }
+
+    /// 按录制时间间隔除以 speed 回放 与实时相同方式解析
+    async fn replay(mut self, frames: Vec<Frame>, speed: f64) {
+        let mut prev = None;
+        for frame in frames {
+            if let Some(prev) = prev {
+                sleep(Duration::from_millis(frame.ts.saturating_sub(prev)).div_f64(speed)).await;
             }
+            prev = Some(frame.ts);
+            match frame.source {
+                FrameSource::Ws => self.emit_quotes(quotes_from_str(WS_QUOTES_PATTERN, &frame.raw), false),
+                FrameSource::Http => self.emit_quotes(quotes_from_str(HTTP_QUOTES_PATTERN, &frame.raw), true),
+            }
+        }
+    }
+
+    fn record(&mut self, source: FrameSource, raw: &str) {
+        if let Some(file) = self.recorder.as_mut() {
+            let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+            let frame = Frame { ts, source, raw: raw.to_string() };
+            if let Err(err) = writeln!(file, "{}", serde_json::to_string(&frame).unwrap()) {
+                error!("record frame failed, {}", err);
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// diff 时仅发送与上次不同的行情
+    fn emit_quotes(&mut self, quotes: Vec<Quote>, diff: bool) {
+        let mut changed = Vec::new();
+        for quote in quotes {
+            if !diff || self.snapshot.get(&quote.symbol) != Some(&quote) {
+                changed.push(quote.clone());
+            }
+            self.snapshot.insert(quote.symbol.clone(), quote);
         }
+        if !changed.is_empty() {
+            self.emit(QuoteEvent::Quotes(changed));
+        }
+    }
+
+    fn emit(&self, event: QuoteEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// 任一订阅代码所在交易所处于交易时段 含集合竞价及美股盘前盘后 无法识别交易所时视为交易中
+    fn in_session(&self) -> bool {
+        let now = Utc::now();
+        self.symbols.iter().any(|symbol| match Investment::from_str(symbol).ok().and_then(|i| i.exchange) {
+            Some(exchange) => !matches!(calendar::status(&exchange, now).phase, Phase::Closed | Phase::Break),
+            None => true,
+        })
     }
 }
 
+/// 实时行情连接参数
+#[derive(Debug, Clone)]
+pub struct WsOptions {
+    /// 重连初始间隔 之后逐次翻倍
+    pub backoff_min: Duration,
+    /// 重连最大间隔 也是每轮 HTTP 轮询的时长
+    pub backoff_max: Duration,
+    /// 超过该时长未收到数据则重连
+    pub stale_timeout: Duration,
+    /// 连续连接失败该次数后降级为 HTTP 轮询
+    pub poll_after_failures: u32,
+    /// HTTP 轮询间隔
+    pub poll_interval: Duration,
+    /// 录制原始行情帧的文件 已存在时追加 可由 Sina::replay 回放
+    pub record: Option<PathBuf>,
+}
+
+impl Default for WsOptions {
+    fn default() -> Self {
+        WsOptions {
+            backoff_min: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+            stale_timeout: Duration::from_secs(180),
+            poll_after_failures: 3,
+            poll_interval: Duration::from_secs(3),
+            record: None,
+        }
+    }
+}
+
+impl WsOptions {
+    fn backoff(&self, failures: u32) -> Duration {
+        self.backoff_min.saturating_mul(2u32.saturating_pow(failures.saturating_sub(1))).min(self.backoff_max)
+    }
+}
+
+fn split_symbols(symbols: &str) -> Vec<String> {
+    symbols.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).map(|s| s.to_string()).collect()
+}
+
 // 港股格式 rt_hk00700
 // 港股指数 rt_hkHSI
 // 美股格式 gb_baba
@@ -488,6 +1203,126 @@ fn fmt_quote_symbols(symbols: &str) -> String {
         .replace("hk", "rt_hk")
         .replace('$', "gb_")
         .replace('.', "")
+        .split(',')
+        .map(|s| match s.strip_prefix("gb_") {
+            Some(code) if US_INDICES.contains(&code) => format!("gb_${}", code),
+            _ => s.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// {"symbol":"sh600000","code":"600000","name":"浦发银行","trade":"7.150","pricechange":"-0.020","changepercent":"-0.279","buy":"7.150","sell":"7.160","settlement":"7.170","open":"7.170","high":"7.180","low":"7.130","volume":14961187,"amount":107009560,"ticktime":"15:00:00","per":4.105,"pb":0.386,"mktcap":20986513.63,"nmc":20986513.63,"turnoverratio":0.05097}
+fn snapshot_from_json(v: &serde_json::Value) -> Snapshot {
+    let num = |key: &str| json_num(&v[key]);
+    let symbol = v["symbol"].as_str().unwrap_or("").to_uppercase();
+    let name = v["name"].as_str().unwrap_or("").to_string();
+    let (limit_up, limit_down) = limit_prices(&symbol, &name, num("settlement")).unwrap_or_default();
+    Snapshot {
+        symbol,
+        name,
+        price: num("trade"),
+        change: num("pricechange"),
+        change_pct: num("changepercent"),
+        close: num("settlement"),
+        open: num("open"),
+        high: num("high"),
+        low: num("low"),
+        turnover: num("volume"),
+        volume: num("amount"),
+        turnover_rate: num("turnoverratio"),
+        pe: num("per"),
+        pb: num("pb"),
+        // 万元
+        market_cap: num("mktcap") * 10000.0,
+        traded_market_cap: num("nmc") * 10000.0,
+        limit_up,
+        limit_down,
+        time: v["ticktime"].as_str().unwrap_or("").to_string(),
+    }
+}
+
+// 节点格式 [名称, 子节点列表或 "", 代码, ...] eg: ["保险Ⅱ","","sw2_490200",...]
+fn sectors_from_nodes(node: &serde_json::Value, sectors: &mut Vec<Sector>) {
+    let items = match node.as_array() {
+        Some(items) => items,
+        None => return,
+    };
+    if let (Some(name), Some(code)) = (items.first().and_then(|v| v.as_str()), items.get(2).and_then(|v| v.as_str())) {
+        if let Some(kind) = SectorKind::from_node(code) {
+            if !sectors.iter().any(|s| s.code == code) {
+                sectors.push(Sector { code: code.to_string(), name: name.to_string(), kind });
+            }
+        }
+    }
+    for item in items.iter() {
+        sectors_from_nodes(item, sectors);
+    }
+}
+
+// {"symbol":"00700","name":"腾讯控股","engname":"TENCENT","lasttrade":"371.000","prevclose":"366.400","open":"380.400","high":"380.400","low":"370.000","volume":"20901992","amount":"7860991814","ticktime":"2022-03-29 16:08:19","pricechange":"4.600","changepercent":"1.255"}
+fn snapshot_from_json_hk(v: &serde_json::Value) -> Snapshot {
+    let num = |key: &str| json_num(&v[key]);
+    Snapshot {
+        symbol: format!("HK{}", v["symbol"].as_str().unwrap_or("")),
+        name: v["name"].as_str().unwrap_or("").to_string(),
+        price: num("lasttrade"),
+        change: num("pricechange"),
+        change_pct: num("changepercent"),
+        close: num("prevclose"),
+        open: num("open"),
+        high: num("high"),
+        low: num("low"),
+        turnover: num("volume"),
+        volume: num("amount"),
+        time: v["ticktime"].as_str().unwrap_or("").to_string(),
+        ..Default::default()
+    }
+}
+
+// IO({"count":"11942","data":[{...}]}); 总数及当前页
+fn us_list_from_str(content: &str) -> Result<(usize, Vec<Snapshot>)> {
+    let json = match (content.find('('), content.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &content[start + 1..end],
+        _ => bail!("parse us snapshot failed, {}", content),
+    };
+    match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(v) => Ok((
+            json_num(&v["count"]) as usize,
+            v["data"].as_array().into_iter().flatten().map(snapshot_from_json_us).collect(),
+        )),
+        Err(err) => bail!("parse us snapshot failed, {}", err),
+    }
+}
+
+// {"name":"Alibaba Group Holding Ltd","cname":"阿里巴巴","symbol":"BABA","price":"101.55","diff":"-1.98","chg":"-1.91","preclose":"103.53","open":"101.09","high":"103.92","low":"99.16","volume":"23504122","mktcap":"275293281157","pe":"27.01","market":"NYSE"}
+fn snapshot_from_json_us(v: &serde_json::Value) -> Snapshot {
+    let num = |key: &str| json_num(&v[key]);
+    let cname = v["cname"].as_str().unwrap_or("");
+    Snapshot {
+        symbol: fmt_us_symbol(v["symbol"].as_str().unwrap_or("")),
+        name: if cname.is_empty() { v["name"].as_str().unwrap_or("") } else { cname }.to_string(),
+        price: num("price"),
+        change: num("diff"),
+        change_pct: num("chg"),
+        close: num("preclose"),
+        open: num("open"),
+        high: num("high"),
+        low: num("low"),
+        turnover: num("volume"),
+        volume: num("volume") * num("price"),
+        pe: num("pe"),
+        market_cap: num("mktcap"),
+        ..Default::default()
+    }
+}
+
+fn json_num(v: &serde_json::Value) -> f64 {
+    match v {
+        serde_json::Value::Number(n) => n.as_f64().unwrap_or(0.0),
+        serde_json::Value::String(s) => s.parse().unwrap_or(0.0),
+        _ => 0.0,
+    }
 }
 
 // 中国平安,51.020,50.790,49.970,51.350,49.800,49.970,49.980,72935539,3688023391.000,155984,49.970,125200,49.960,95800,49.950,48800,49.940,32300,49.930,174297,49.980,10800,49.990,86300,50.000,3100,50.010,53700,50.020,2022-01-28,15:00:00,00,
@@ -495,7 +1330,7 @@ fn quote_from_str(str: &str) -> Quote {
     let values: Vec<&str> = str.split(',').collect::<Vec<&str>>();
     Quote {
         symbol: "".to_string(),
-        name: values.get(0).unwrap_or(&"").to_string(),
+        name: values.first().unwrap_or(&"").to_string(),
         now: values.get(3).unwrap_or(&"").parse().unwrap_or(0.0),
         close: values.get(2).unwrap_or(&"").parse().unwrap_or(0.0),
         open: values.get(1).unwrap_or(&"").parse().unwrap_or(0.0),
@@ -505,8 +1340,8 @@ fn quote_from_str(str: &str) -> Quote {
         sell: values.get(7).unwrap_or(&"").parse().unwrap_or(0.0),
         turnover: values.get(8).unwrap_or(&"").parse().unwrap_or(0.0),
         volume: values.get(9).unwrap_or(&"").parse().unwrap_or(0.0),
-        date: values.get(30).unwrap_or(&"").to_string(),
-        time: values.get(31).unwrap_or(&"").to_string(),
+        time: calendar::parse_local(&Exchange::Sse, values.get(30).unwrap_or(&""), values.get(31).unwrap_or(&"")),
+        currency: Currency::Cny,
     }
 }
 
@@ -525,8 +1360,8 @@ fn quote_from_str_hk(str: &str) -> Quote {
         sell: values.get(7).unwrap_or(&"").parse().unwrap_or(0.0),
         turnover: values.get(12).unwrap_or(&"").parse().unwrap_or(0.0),
         volume: values.get(11).unwrap_or(&"").parse().unwrap_or(0.0),
-        date: values.get(17).unwrap_or(&"").replace('/', "-"),
-        time: values.get(18).unwrap_or(&"").to_string(),
+        time: calendar::parse_local(&Exchange::HKex, values.get(17).unwrap_or(&""), values.get(18).unwrap_or(&"")),
+        currency: Currency::Hkd,
     }
 }
 
@@ -536,7 +1371,7 @@ fn quote_from_str_us(str: &str) -> Quote {
     let datetime = values.get(3).unwrap_or(&"").split(' ').collect::<Vec<&str>>();
     Quote {
         symbol: "".to_string(),
-        name: values.get(0).unwrap_or(&"").to_string(),
+        name: values.first().unwrap_or(&"").to_string(),
         now: values.get(1).unwrap_or(&"").parse().unwrap_or(0.0),
         close: values.get(26).unwrap_or(&"").parse().unwrap_or(0.0),
         open: values.get(5).unwrap_or(&"").parse().unwrap_or(0.0),
@@ -546,17 +1381,41 @@ fn quote_from_str_us(str: &str) -> Quote {
         sell: values.get(7).unwrap_or(&"").parse().unwrap_or(0.0),
         turnover: values.get(10).unwrap_or(&"").parse().unwrap_or(0.0),
         volume: values.get(30).unwrap_or(&"").parse().unwrap_or(0.0),
-        date: datetime.get(0).unwrap_or(&"").to_string(),
-        time: datetime.get(1).unwrap_or(&"").to_string(),
+        // 更新时间为北京时间 转为美东时间
+        time: calendar::parse_local(&Exchange::Sse, datetime.first().unwrap_or(&""), datetime.get(1).unwrap_or(&""))
+            .map(|t| t.with_timezone(&Exchange::Nasdaq.timezone()).fixed_offset()),
+        currency: Currency::Usd,
     }
 }
 
+// 15:59:58,0.9036,0.9040,0.9034,34,0.9035,0.9047,0.9026,0.9036,港币兑人民币,...
+fn rates_from_str(str: &str) -> Rates {
+    let mut rates = Rates::default();
+    let regex = Regex::new(r#"hq_str_fx_s(\w{3})cny="([^"]*)""#).unwrap();
+    for caps in regex.captures_iter(str) {
+        let values = caps.get(2).unwrap().as_str().split(',').collect::<Vec<&str>>();
+        let rate = [8, 1].iter().find_map(|i| values.get(*i)?.parse::<f64>().ok().filter(|r| *r > 0.0)).unwrap_or(0.0);
+        match caps.get(1).unwrap().as_str() {
+            "hkd" => rates.hkd = rate,
+            "usd" => rates.usd = rate,
+            _ => {}
+        }
+    }
+    rates
+}
+
 fn quotes_from_str(regex: &str, str: &str) -> Vec<Quote> {
     let mut quotes = Vec::new();
     let regex = Regex::new(regex).unwrap();
     for caps in regex.captures_iter(str) {
         let quote_str = caps.get(2).unwrap().as_str();
-        let invest = Investment::from_str(caps.get(1).unwrap().as_str()).unwrap();
+        let invest = match Investment::from_str(caps.get(1).unwrap().as_str()) {
+            Ok(invest) => invest,
+            Err(err) => {
+                debug!("{}", err);
+                continue;
+            }
+        };
         let mut quote = match Exchange::from_str(&invest.symbol[..2]) {
             Ok(ex) => match ex {
                 Exchange::Sse | Exchange::SZse | Exchange::Bse => quote_from_str(quote_str),
@@ -570,6 +1429,8 @@ fn quotes_from_str(regex: &str, str: &str) -> Vec<Quote> {
             }
         };
 
+        // B 股以外币计价
+        quote.currency = Currency::from_symbol(&invest.symbol);
         quote.symbol = invest.symbol;
         quotes.push(quote);
     }
@@ -577,9 +1438,603 @@ fn quotes_from_str(regex: &str, str: &str) -> Vec<Quote> {
     quotes
 }
 
+/// 新浪财务报表 首行为日期 其余每行为科目及各期数值
+#[derive(Debug, Default)]
+struct Report {
+    dates: Vec<NaiveDate>,
+    rows: HashMap<String, Vec<f64>>,
+}
+
+impl Report {
+    /// 未找到日期行时为布局错误 日期为空或无法解析的列整列忽略
+    fn parse(content: &str) -> Result<Report> {
+        let table = Table::select(content, "#BalanceSheetNewTable0")?;
+        let mut rows = table.labeled_rows();
+        let dates = match rows.iter().position(|(label, _)| label.ends_with("日期")) {
+            Some(i) => rows.remove(i).1.iter().map(|v| parse_date(v)).collect::<Vec<_>>(),
+            None => vec![],
+        };
+        let mut report = Report { dates: dates.iter().flatten().copied().collect(), ..Default::default() };
+        for (label, values) in rows {
+            let values = values.iter().zip(dates.iter()).filter(|(_, d)| d.is_some()).map(|(v, _)| report_num(v));
+            report.rows.insert(label, values.collect());
+        }
+        if report.dates.is_empty() {
+            bail!(LayoutError {
+                table: "#BalanceSheetNewTable0".to_string(),
+                missing: vec!["报表日期".to_string()],
+                row: None
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// 按日期合并 同一日期取先出现的 日期降序
+    fn merge(reports: &[Report]) -> Report {
+        let mut columns = Vec::new();
+        for report in reports.iter() {
+            for (i, date) in report.dates.iter().enumerate() {
+                if !columns.iter().any(|(d, _, _)| d == date) {
+                    columns.push((*date, report, i));
+                }
+            }
+        }
+        columns.sort_by_key(|(date, _, _)| Reverse(*date));
+
+        let mut merged = Report { dates: columns.iter().map(|(date, _, _)| *date).collect(), ..Default::default() };
+        for label in reports.iter().flat_map(|r| r.rows.keys()) {
+            if !merged.rows.contains_key(label) {
+                let values = columns.iter().map(|(_, r, i)| r.rows.get(label).and_then(|v| v.get(*i)).copied());
+                merged.rows.insert(label.to_string(), values.map(|v| v.unwrap_or(0.0)).collect());
+            }
+        }
+
+        merged
+    }
+
+    /// 自 latest 最早一年起逐年向前获取 至满 limit 期或某年无数据 该年已含一季报时从上一年开始
+    async fn backfill<F, Fut>(latest: Report, limit: usize, fetch: F) -> Report
+    where
+        F: Fn(i32) -> Fut,
+        Fut: Future<Output = Option<Report>>,
+    {
+        let mut year = match latest.dates.iter().min() {
+            Some(date) if date.month() <= 3 => date.year() - 1,
+            Some(date) => date.year(),
+            None => return latest,
+        };
+        let mut merged = latest;
+        while merged.dates.len() < limit {
+            let before = merged.dates.len();
+            match fetch(year).await {
+                Some(report) => merged = Report::merge(&[merged, report]),
+                None => break,
+            }
+            if merged.dates.len() == before {
+                break;
+            }
+            year -= 1;
+        }
+
+        merged.truncate(limit)
+    }
+
+    fn truncate(mut self, n: usize) -> Report {
+        self.dates.truncate(n);
+        for values in self.rows.values_mut() {
+            values.truncate(n);
+        }
+        self
+    }
+
+    fn index(&self, date: NaiveDate) -> Option<usize> {
+        self.dates.iter().position(|d| *d == date)
+    }
+
+    /// 依次按科目名查找第 i 期数值 未找到为 0
+    fn get(&self, i: usize, labels: &[&str]) -> f64 {
+        labels
+            .iter()
+            .filter_map(|label| self.rows.get(&normalize_label(label)))
+            .find_map(|values| values.get(i).copied())
+            .unwrap_or(0.0)
+    }
+}
+
+/// 财务摘要 每期以截止日期行开始 值带 元 单位 截止日期无效的期跳过
+fn financials_from_str(content: &str, limit: usize) -> Result<Vec<Financial>> {
+    let table = Table::select(content, "#FundHoldSharesTable")?;
+    table.expect_labels(&["截止日期", "每股收益", "净利润"])?;
+    Ok(table
+        .blocks("截止日期", &[])?
+        .iter()
+        .filter_map(|block| {
+            let num = |label: &str| report_num(&block.labels.get(label).replace('元', ""));
+            Some(Financial {
+                date: parse_date(block.labels.get("截止日期"))?,
+                ps_net_assets: num("每股净资产"),
+                eps: num("每股收益"),
+                ps_capital_reserve: num("每股资本公积金"),
+                total_revenue: num("主营业务收入"),
+                net_profit: num("净利润"),
+                ..Default::default()
+            })
+        })
+        .take(limit)
+        .collect())
+}
+
+/// 十大股东及十大流通股东 持股比例分别为占总股本及流通股
+fn holder_from_record(record: &Record) -> Holder {
+    Holder {
+        name: record.get("股东名称").to_string(),
+        shares: report_num(record.get("持股数量")),
+        percent: report_num(match record.get("持股比例") {
+            "" => record.get("占流通股比例"),
+            percent => percent,
+        }),
+        shares_type: record.get("股本性质").to_string(),
+    }
+}
+
+/// 股东页 每期为截止日期、股东总数等标签行及十大股东明细 截止日期无效的期跳过
+fn structures_from_str(content: &str) -> Result<Vec<Structure>> {
+    let table = Table::select(content, "#Table1")?;
+    table.expect_labels(&["截止日期", "股东总数"])?;
+    Ok(table
+        .blocks("截止日期", &["股东名称", "持股数量"])?
+        .iter()
+        .filter_map(|block| {
+            Some(Structure {
+                date: parse_date(block.labels.get("截止日期"))?,
+                holders_num: num_from_str(block.labels.get("股东总数")),
+                shares_avg: num_from_str(block.labels.get("平均持股数")),
+                holders_ten: block.records.iter().map(holder_from_record).collect(),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+fn floats_from_str(content: &str) -> Result<Vec<(NaiveDate, Vec<Holder>)>> {
+    Ok(Table::select(content, "#CirculateShareholderTable")?
+        .blocks("截止日期", &["股东名称", "持股数量"])?
+        .iter()
+        .filter_map(|block| {
+            let date = parse_date(block.labels.get("截止日期"))?;
+            Some((date, block.records.iter().map(holder_from_record).collect()))
+        })
+        .collect())
+}
+
+fn funds_from_str(content: &str) -> Result<Vec<(NaiveDate, Vec<Fund>)>> {
+    Ok(Table::select(content, "#FundHoldSharesTable")?
+        .blocks("截止日期", &["基金名称", "持仓数量"])?
+        .iter()
+        .filter_map(|block| {
+            let date = parse_date(block.labels.get("截止日期"))?;
+            let funds = block
+                .records
+                .iter()
+                .map(|r| Fund {
+                    name: r.get("基金名称").to_string(),
+                    code: r.get("基金代码").to_string(),
+                    shares: report_num(r.get("持仓数量")),
+                    percent: report_num(r.get("占流通股比例")),
+                    value: report_num(r.get("持股市值")),
+                    nav_percent: report_num(r.get("占净值比例")),
+                })
+                .collect();
+            Some((date, funds))
+        })
+        .collect())
+}
+
+/// 两层表头 分红方案(每10股) 下分 送股 转增 派息
+fn dividends_from_str(content: &str) -> Result<Vec<Dividend>> {
+    let records = Table::select(content, "#sharebonus_1")?.records(&[
+        "公告日期",
+        "送股",
+        "转增",
+        "派息",
+        "除权除息日",
+        "股权登记日",
+    ])?;
+    Ok(records
+        .iter()
+        .filter_map(|r| {
+            Some(Dividend {
+                date: parse_date(r.get("公告日期"))?,
+                date_record: parse_date(r.get("股权登记日")),
+                date_dividend: parse_date(r.get("除权除息日")),
+                shares_dividend: report_num(r.get("送股")),
+                shares_into: report_num(r.get("转增")),
+                money: report_num(r.get("派息")),
+            })
+        })
+        .collect())
+}
+
+fn corp_from_str(content: &str, profile: &mut Profile) -> Result<()> {
+    let fields = Table::select(content, "#comInfo1")?.fields();
+    if !fields.contains("公司名称") {
+        bail!(LayoutError { table: "#comInfo1".to_string(), missing: vec!["公司名称".to_string()], row: None });
+    }
+    let field = |label: &str| fields.get(label).to_string();
+    profile.name = field("公司名称");
+    profile.english_name = field("公司英文名称");
+    profile.market = field("上市市场");
+    profile.listing_date = parse_date(fields.get("上市日期"));
+    profile.listing_price = report_num(fields.get("发行价格"));
+    profile.ipo.underwriter = field("主承销商");
+    profile.founded_date = parse_date(fields.get("成立日期"));
+    profile.registered_capital = num_with_unit(fields.get("注册资本"));
+    profile.org_type = field("机构类型");
+    profile.org_form = field("组织形式");
+    profile.legal_representative =
+        Some(field("法人代表")).filter(|v| !v.is_empty()).unwrap_or_else(|| field("法定代表人"));
+    profile.chairman = field("董事长");
+    profile.general_manager = field("总经理");
+    profile.secretary = field("董事会秘书");
+    profile.employees = num_from_str(fields.get("员工人数"));
+    profile.phone = field("公司电话");
+    profile.fax = field("公司传真");
+    profile.email = field("公司电子邮箱");
+    profile.secretary_phone = field("董秘电话");
+    profile.secretary_fax = field("董秘传真");
+    profile.secretary_email = field("董秘电子邮箱");
+    profile.website = field("公司网址");
+    profile.postcode = field("邮政编码");
+    profile.disclosure_website = field("信息披露网址");
+    profile.used_name = field("证券简称更名历史");
+    profile.registered_address = field("注册地址");
+    profile.business_address = field("办公地址");
+    profile.introduction = field("公司简介");
+    profile.business = field("经营范围");
+
+    Ok(())
+}
+
+/// 发行数量单位万股 金额单位万元
+fn ipo_from_str(content: &str, profile: &mut Profile) -> Result<()> {
+    let fields = Table::select(content, "#comInfo1")?.fields();
+    if !fields.contains("发行方式") {
+        bail!(LayoutError { table: "#comInfo1".to_string(), missing: vec!["发行方式".to_string()], row: None });
+    }
+    let ipo = &mut profile.ipo;
+    if ipo.underwriter.is_empty() {
+        ipo.underwriter = fields.get("主承销商").to_string();
+    }
+    ipo.sponsor = fields.get("上市推荐人").to_string();
+    ipo.method = fields.get("发行方式").to_string();
+    ipo.pe = report_num(fields.get("发行市盈率"));
+    ipo.shares = report_num(fields.get("实际发行量")) * 10_000.0;
+    ipo.proceeds = report_num(fields.get("实际募集资金")) * 10_000.0;
+    ipo.expenses = report_num(fields.get("发行费用总额")) * 10_000.0;
+    ipo.prospectus_date = parse_date(fields.get("招股公告日"));
+    if profile.listing_price == 0.0 {
+        profile.listing_price = report_num(fields.get("每股发行价"));
+    }
+
+    Ok(())
+}
+
+/// 高管、董事会、监事会各一表 首行为标题 之后为 姓名 职务 起始日期 终止日期
+fn managers_from_str(content: &str, profile: &mut Profile) -> Result<()> {
+    for table in Table::select_all(content, "#comInfo1")? {
+        let title = table.rows().next().map(|row| row.concat()).unwrap_or_default();
+        let managers = match title {
+            _ if title.contains("高管") => &mut profile.executives,
+            _ if title.contains("董事会") => &mut profile.directors,
+            _ if title.contains("监事会") => &mut profile.supervisors,
+            _ => continue,
+        };
+        for r in table.records(&["姓名", "职务", "起始日期", "终止日期"])? {
+            managers.push(Manager {
+                name: r.get("姓名").to_string(),
+                title: r.get("职务").to_string(),
+                start: parse_date(r.get("起始日期")),
+                end: parse_date(r.get("终止日期")),
+            });
+        }
+    }
+
+    // 资料页未提供时取在任董事长及总经理
+    let current = |managers: &[Manager], title: &str| {
+        managers
+            .iter()
+            .find(|m| m.end.is_none() && m.title.contains(title) && !m.title.contains('副'))
+            .map_or(String::new(), |m| m.name.to_string())
+    };
+    if profile.chairman.is_empty() {
+        profile.chairman = current(&profile.directors, "董事长");
+    }
+    if profile.general_manager.is_empty() {
+        profile.general_manager = current(&profile.executives, "总经理");
+    }
+
+    Ok(())
+}
+
+/// 股本结构分多个表格 每表首列为标签 之后每列为一次变动
+fn capitals_from_str(content: &str) -> Result<Vec<Capital>> {
+    let tables = Table::select_all(content, "table[id^='StockStructureNewTable']")?;
+    let mut capitals = Vec::new();
+    for table in tables.iter() {
+        table.expect_labels(&["变动日期", "总股本"])?;
+        let rows = table.labeled_rows();
+        let cell = |label: &str, i: usize| {
+            rows.iter().find(|(l, _)| l == label).and_then(|(_, v)| v.get(i)).map_or("", |v| v.as_str())
+        };
+        for i in 0..rows.iter().find(|(l, _)| l == "变动日期").map_or(0, |(_, v)| v.len()) {
+            let date = match parse_date(cell("变动日期", i)) {
+                Some(date) => date,
+                None => continue,
+            };
+            capitals.push(Capital {
+                date,
+                reason: cell("变动原因", i).to_string(),
+                total: num_with_unit(cell("总股本", i)),
+                tradable: num_with_unit(cell("流通股", i)),
+                tradable_a: num_with_unit(cell("流通A股", i)),
+                restricted_a: num_with_unit(cell("限售A股", i)),
+                h_shares: num_with_unit(cell("流通H股", i)),
+            });
+        }
+    }
+
+    Ok(capitals)
+}
+
+/// 解禁列表 数量单位万股 市值单位亿元
+fn unlocks_from_str(content: &str) -> Result<Vec<Unlock>> {
+    let records = Table::select(content, "#dataTable")?.records(&["解禁日期", "解禁数量", "解禁股流通市值"])?;
+    Ok(records
+        .iter()
+        .filter_map(|r| {
+            Some(Unlock {
+                date: parse_date(r.get("解禁日期"))?,
+                shares: report_num(r.get("解禁数量")) * 10_000.0,
+                market_value: report_num(r.get("解禁股流通市值")) * 100_000_000.0,
+                kind: match r.get("上市批次") {
+                    "" => r.get("限售股类型").to_string(),
+                    kind => kind.to_string(),
+                },
+            })
+        })
+        .collect())
+}
+
+/// 按万、亿单位换算 eg: 1828024.1410万股 / 1828024万元
+fn num_with_unit(str: &str) -> f64 {
+    match num_from_str(str) {
+        n if str.contains('亿') => n * 100_000_000.0,
+        n if str.contains('万') => n * 10_000.0,
+        n => n,
+    }
+}
+
+fn report_num(str: &str) -> f64 {
+    str.replace(',', "").trim().parse::<f64>().unwrap_or(0.0)
+}
+
+/// 取首个数值 忽略千分位及单位 eg: 217,155户
 fn num_from_str(str: &str) -> f64 {
-    match Regex::new(r"\d+").unwrap().captures(str) {
-        Some(caps) => caps.get(0).unwrap().as_str().parse::<f64>().unwrap_or(0.0),
-        None => 0.0,
+    static NUM: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d[\d,]*(\.\d+)?").unwrap());
+    NUM.find(str).map_or(0.0, |m| report_num(m.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_parse() {
+        let report = Report::parse(
+            r#"<table id="BalanceSheetNewTable0"><tbody>
+            <tr><td>报表日期</td><td>2021-12-31</td><td></td><td>2020-12-31</td></tr>
+            <tr><td>流动资产</td></tr>
+            <tr><td><a href="/x">五、净利润</a></td><td>1,234.5</td><td>9</td><td>--</td></tr>
+            <tr><td>净资产收益率(%)</td><td>12.3</td><td></td><td>11.1</td></tr>
+            </tbody></table>"#,
+        )
+        .unwrap();
+        assert_eq!(report.dates.iter().map(|d| d.to_string()).collect::<Vec<_>>(), vec!["2021-12-31", "2020-12-31"]);
+        assert_eq!(report.index(parse_date("2020-12-31").unwrap()), Some(1));
+        assert_eq!(report.get(0, &["净利润"]), 1234.5);
+        assert_eq!(report.get(1, &["净利润"]), 0.0);
+        assert_eq!(report.get(1, &["净资产收益率"]), 11.1);
+        assert_eq!(report.get(0, &["营业收入"]), 0.0);
+        let earlier = Report::parse(
+            r#"<table id="BalanceSheetNewTable0">
+            <tr><td>报表日期</td><td>2020-12-31</td><td>2020-09-30</td></tr>
+            <tr><td>营业收入</td><td>8</td><td>6</td></tr>
+            </table>"#,
+        )
+        .unwrap();
+        let merged = Report::merge(&[report, earlier]).truncate(3);
+        assert_eq!(
+            merged.dates.iter().map(|d| d.to_string()).collect::<Vec<_>>(),
+            vec!["2021-12-31", "2020-12-31", "2020-09-30"]
+        );
+        assert_eq!(merged.get(1, &["净利润"]), 0.0);
+        assert_eq!(merged.get(2, &["营业收入"]), 6.0);
+        assert_eq!(merged.get(0, &["营业收入"]), 0.0);
+        assert!(Report::parse(r#"<table id="BalanceSheetNewTable0"><tr><td>流动资产</td><td>1</td></tr></table>"#)
+            .unwrap_err()
+            .is::<LayoutError>());
+    }
+
+    #[tokio::test]
+    async fn test_report_backfill() {
+        let report = |dates: &[&str]| Report {
+            dates: dates.iter().map(|d| parse_date(d).unwrap()).collect(),
+            ..Default::default()
+        };
+        let year = |year: i32| {
+            let dates = ["12-31", "09-30", "06-30", "03-31"].map(|d| format!("{}-{}", year, d));
+            report(&dates.iter().map(|d| d.as_str()).collect::<Vec<_>>())
+        };
+        let latest = || report(&["2022-06-30", "2022-03-31", "2021-12-31", "2021-09-30", "2021-06-30"]);
+        let fetched = std::sync::Mutex::new(vec![]);
+        let fetch = |y: i32| {
+            fetched.lock().unwrap().push(y);
+            async move { Some(y).filter(|y| *y >= 2019).map(year) }
+        };
+
+        // 2021 年缺一季报 仍需获取
+        let merged = Report::backfill(latest(), 8, fetch).await;
+        assert_eq!(merged.dates.len(), 8);
+        assert_eq!(merged.dates.last(), parse_date("2020-09-30").as_ref());
+        assert_eq!(*fetched.lock().unwrap(), vec![2021, 2020]);
+
+        fetched.lock().unwrap().clear();
+        assert_eq!(Report::backfill(latest(), 40, fetch).await.dates.len(), 14);
+        assert_eq!(*fetched.lock().unwrap(), vec![2021, 2020, 2019, 2018]);
+
+        fetched.lock().unwrap().clear();
+        let latest = report(&["2022-03-31", "2021-12-31", "2021-09-30", "2021-06-30", "2021-03-31"]);
+        assert_eq!(Report::backfill(latest, 8, fetch).await.dates.len(), 8);
+        assert_eq!(*fetched.lock().unwrap(), vec![2020]);
+    }
+
+    #[test]
+    fn test_structures_from_str() {
+        let structures = structures_from_str(
+            r#"<table id="Table1"><tbody>
+            <tr><td><div><strong>截止日期</strong></div></td><td>2021-12-31</td></tr>
+            <tr><td>股东总数</td><td><a href="/x">217,155户</a></td></tr>
+            <tr><td>平均持股数</td><td>84115股</td></tr>
+            <tr><td>编号</td><td>股东名称</td><td>持股数量(股)</td><td>持股比例(%)</td><td>股本性质</td></tr>
+            <tr><td><div>1</div></td><td><div><a href="/h">香港中央结算(代理人)有限公司</a></div></td><td><div>6,000,000</div></td><td><div>32.78</div></td><td><div>H股</div></td></tr>
+            <tr><td>截止日期</td><td>2021-09-30</td></tr>
+            <tr><td>股东总数</td><td>200000</td></tr>
+            </tbody></table>"#,
+        )
+        .unwrap();
+        assert_eq!(structures.len(), 2);
+        assert_eq!(structures[0].date, parse_date("2021-12-31").unwrap());
+        assert_eq!(structures[0].holders_num, 217155.0);
+        assert_eq!(structures[0].shares_avg, 84115.0);
+        assert_eq!(structures[0].holders_ten.len(), 1);
+        assert_eq!(structures[0].holders_ten[0].name, "香港中央结算(代理人)有限公司");
+        assert_eq!(structures[0].holders_ten[0].shares, 6000000.0);
+        assert_eq!(structures[0].holders_ten[0].percent, 32.78);
+        assert!(structures[1].holders_ten.is_empty());
+    }
+
+    #[test]
+    fn test_capital_parse() {
+        let capitals = capitals_from_str(
+            r#"<table id="StockStructureNewTable0"><tbody>
+            <tr><td>变动日期</td><td>2021-12-31</td><td>2020-06-30</td></tr>
+            <tr><td>变动原因</td><td>回购</td><td>定期报告</td></tr>
+            <tr><td>总股本</td><td>1828024.1410万股</td><td>1828024.1410万股</td></tr>
+            <tr><td>流通A股</td><td>1083266.4498万股</td><td>--</td></tr>
+            <tr><td>限售A股</td><td>--</td><td>5000万股</td></tr>
+            </tbody></table>"#,
+        )
+        .unwrap();
+        assert_eq!(capitals.len(), 2);
+        assert_eq!(capitals[0].reason, "回购");
+        assert_eq!(capitals[0].total, 18280241410.0);
+        assert_eq!(capitals[0].tradable_a, 10832664498.0);
+        assert_eq!(capitals[1].tradable_a, 0.0);
+        assert_eq!(capitals[1].restricted_a, 50000000.0);
+
+        let unlocks = unlocks_from_str(
+            r#"<table id="dataTable"><tr><th>代码</th><th>名称</th><th>解禁日期</th><th>解禁数量(万股)</th><th>解禁股流通市值(亿元)</th><th>上市批次</th><th>公告日期</th></tr>
+            <tr><td>601318</td><td>中国平安</td><td>2022-06-30</td><td>1,200.5</td><td>6.5</td><td>股权激励限售流通股</td><td>2022-06-25</td></tr></table>"#,
+        )
+        .unwrap();
+        assert_eq!(unlocks.len(), 1);
+        assert_eq!(unlocks[0].date, parse_date("2022-06-30").unwrap());
+        assert_eq!(unlocks[0].shares, 12005000.0);
+        assert_eq!(unlocks[0].market_value, 650000000.0);
+        assert_eq!(unlocks[0].kind, "股权激励限售流通股");
+    }
+
+    #[test]
+    fn test_profile_parse() {
+        let mut profile = Profile::default();
+        corp_from_str(
+            r#"<table id="comInfo1">
+            <tr><td>公司名称：</td><td colspan="3">中国平安保险(集团)股份有限公司</td></tr>
+            <tr><td>上市市场：</td><td>上海证券交易所</td><td>上市日期：</td><td><a href="/d">2007-03-01</a></td></tr>
+            <tr><td>发行价格：</td><td>33.80</td><td>主承销商：</td><td>中国国际金融有限公司</td></tr>
+            <tr><td>成立日期：</td><td>1988-03-21</td><td>注册资本：</td><td>1828024万元</td></tr>
+            <tr><td>董事会秘书：</td><td>盛瑞生</td><td>公司网址：</td><td><a href="http://www.pingan.cn">www.pingan.cn</a></td></tr>
+            <tr><td>注册地址：</td><td colspan="3">广东省深圳市福田区</td></tr>
+            </table>"#,
+            &mut profile,
+        )
+        .unwrap();
+        assert_eq!(profile.name, "中国平安保险(集团)股份有限公司");
+        assert_eq!(profile.market, "上海证券交易所");
+        assert_eq!(profile.listing_date, parse_date("2007-03-01"));
+        assert_eq!(profile.listing_price, 33.8);
+        assert_eq!(profile.ipo.underwriter, "中国国际金融有限公司");
+        assert_eq!(profile.registered_capital, 18280240000.0);
+        assert_eq!(profile.secretary, "盛瑞生");
+        assert_eq!(profile.website, "www.pingan.cn");
+        assert_eq!(profile.registered_address, "广东省深圳市福田区");
+
+        ipo_from_str(
+            r#"<table id="comInfo1">
+            <tr><td>发行方式：</td><td>网下询价</td><td>发行市盈率(按发行后总股数)：</td><td>29.47</td></tr>
+            <tr><td>实际发行量(万股)：</td><td>115000</td><td>实际募集资金合计(万元)：</td><td>3,887,000</td></tr>
+            </table>"#,
+            &mut profile,
+        )
+        .unwrap();
+        assert_eq!(profile.ipo.method, "网下询价");
+        assert_eq!(profile.ipo.pe, 29.47);
+        assert_eq!(profile.ipo.shares, 1150000000.0);
+        assert_eq!(profile.ipo.proceeds, 38870000000.0);
+
+        managers_from_str(
+            r#"<table id="comInfo1">
+            <tr><th colspan="4">历届董事会成员</th></tr>
+            <tr><td>姓名</td><td>职务</td><td>起始日期</td><td>终止日期</td></tr>
+            <tr><td>马明哲</td><td>董事长</td><td>1995-03-01</td><td>--</td></tr>
+            <tr><td>谢永林</td><td>副董事长</td><td>2019-06-01</td><td>--</td></tr>
+            </table>
+            <table id="comInfo1">
+            <tr><th colspan="4">历届高管成员</th></tr>
+            <tr><td>姓名</td><td>职务</td><td>起始日期</td><td>终止日期</td></tr>
+            <tr><td>谢永林</td><td>总经理</td><td>2020-01-01</td><td>--</td></tr>
+            <tr><td>任汇川</td><td>总经理</td><td>2011-03-01</td><td>2019-12-01</td></tr>
+            </table>"#,
+            &mut profile,
+        )
+        .unwrap();
+        assert_eq!(profile.directors.len(), 2);
+        assert_eq!(profile.executives.len(), 2);
+        assert!(profile.supervisors.is_empty());
+        assert_eq!(profile.executives[1].end, parse_date("2019-12-01"));
+        assert_eq!(profile.chairman, "马明哲");
+        assert_eq!(profile.general_manager, "谢永林");
+    }
+
+    #[test]
+    fn test_rates_from_str() {
+        let rates = rates_from_str(
+            r#"var hq_str_fx_shkdcny="15:59:58,0.9036,0.9040,0.9034,34,0.9035,0.9047,0.9026,0.9036,港币兑人民币,0.0222,0.0002,0.002214,Sina,0.9396,0.8100,*+-++-++,2022-04-12";
+var hq_str_fx_susdcny="15:59:59,6.3700,6.3710,6.3660,70,6.3680,6.3740,6.3650,,美元兑人民币,0.0628,0.0040,0.000628,Sina,6.7230,6.3050,*--+--+-,2022-04-12";"#,
+        );
+        assert_eq!(rates, Rates { hkd: 0.9036, usd: 6.37 });
+    }
+
+    #[test]
+    fn test_us_list_from_str() {
+        let (count, snapshots) = us_list_from_str(
+            r#"IO({"count":"11942","data":[{"name":"Alibaba Group Holding Ltd","cname":"阿里巴巴","symbol":"BABA","price":"101.55","diff":"-1.98","chg":"-1.91","preclose":"103.53","open":"101.09","high":"103.92","low":"99.16","volume":"200","mktcap":"275293281157","pe":"27.01","market":"NYSE"}]});"#,
+        )
+        .unwrap();
+        assert_eq!(count, 11942);
+        assert_eq!(snapshots[0].name, "阿里巴巴");
+        assert_eq!(snapshots[0].volume, 20310.0);
+        assert!(us_list_from_str("<html>").is_err());
     }
 }
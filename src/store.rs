@@ -0,0 +1,491 @@
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Result};
+use chrono::{NaiveDate, NaiveTime};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::invest::{
+    calendar,
+    currency::{Currency, Rates},
+    quote::{Bar, Quote},
+    statement::{BalanceSheet, CashFlow, IncomeStatement},
+    stock::{Capital, Dividend, Financial, Press, Profile, Structure, Unlock},
+    Exchange, Investment, Market,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS investments (
+    symbol TEXT PRIMARY KEY,
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    sector TEXT
+);
+CREATE TABLE IF NOT EXISTS bars (
+    symbol TEXT NOT NULL,
+    date TEXT NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    turnover REAL NOT NULL,
+    PRIMARY KEY (symbol, date)
+);
+CREATE TABLE IF NOT EXISTS records (
+    symbol TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    key TEXT NOT NULL,
+    date TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (symbol, kind, key)
+);
+";
+
+/// 本地数据存储
+///
+/// K线按列存储 便于区间查询 其他信息以 JSON 存储
+#[derive(Debug)]
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        // 兼容旧版数据库
+        let has_sector =
+            conn.prepare("SELECT 1 FROM pragma_table_info('investments') WHERE name = 'sector'")?.exists([])?;
+        if !has_sector {
+            conn.execute_batch("ALTER TABLE investments ADD COLUMN sector TEXT")?;
+        }
+        Ok(Store { conn })
+    }
+
+    /// 默认路径 ~/.rains/rains.db
+    pub fn open_default() -> Result<Self> {
+        let dir = data_dir()?;
+        fs::create_dir_all(&dir)?;
+        Self::open(dir.join("rains.db"))
+    }
+
+    /// 所属板块以 JSON 存储
+    pub fn save_investment(&self, invest: &Investment) -> Result<()> {
+        let sector = invest.sector.as_ref().map(serde_json::to_string).transpose()?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO investments (symbol, code, name, sector) VALUES (?1, ?2, ?3, ?4)",
+            params![invest.symbol, invest.code, invest.name, sector],
+        )?;
+        Ok(())
+    }
+
+    pub fn investment(&self, symbol: &str) -> Result<Option<Investment>> {
+        Ok(self.search(symbol)?.into_iter().find(|invest| invest.symbol == symbol.to_uppercase()))
+    }
+
+    /// 按代码/名称搜索已同步的投资品
+    pub fn search(&self, query: &str) -> Result<Vec<Investment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, code, name, sector FROM investments WHERE symbol LIKE ?1 OR code LIKE ?1 OR name LIKE ?1 OR sector LIKE ?1 ORDER BY symbol",
+        )?;
+        let rows = stmt.query_map(params![format!("%{}%", query.to_uppercase())], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+
+        let mut investments = Vec::new();
+        for row in rows {
+            let (symbol, code, name, sector) = row?;
+            // 旧版为行业名称文本 重新同步后覆盖
+            let sector = sector.and_then(|s| serde_json::from_str(&s).ok());
+            investments.push(Investment {
+                exchange: Exchange::from_str(&symbol[..2]).ok(),
+                market: Some(Market::Stock),
+                symbol,
+                code,
+                name,
+                sector,
+            });
+        }
+
+        Ok(investments)
+    }
+
+    pub fn last_bar_date(&self, symbol: &str) -> Result<Option<NaiveDate>> {
+        let date = self
+            .conn
+            .query_row("SELECT MAX(date) FROM bars WHERE symbol = ?1", params![symbol], |row| row.get(0))
+            .optional()?;
+        Ok(date.flatten())
+    }
+
+    /// 返回新增条数
+    pub fn save_bars(&mut self, symbol: &str, bars: &[Bar]) -> Result<usize> {
+        let last = self.last_bar_date(symbol)?;
+        let tx = self.conn.transaction()?;
+        for b in bars.iter() {
+            tx.execute(
+                "INSERT OR REPLACE INTO bars (symbol, date, open, high, low, close, turnover) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![symbol, b.date, b.open, b.high, b.low, b.close, b.turnover],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(bars.iter().filter(|b| last.is_none_or(|last| b.date > last)).count())
+    }
+
+    /// 按日期升序
+    pub fn bars(&self, symbol: &str) -> Result<Vec<Bar>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT date, open, high, low, close, turnover FROM bars WHERE symbol = ?1 ORDER BY date")?;
+        let rows = stmt.query_map(params![symbol], |row| {
+            Ok(Bar {
+                date: row.get(0)?,
+                open: row.get(1)?,
+                high: row.get(2)?,
+                low: row.get(3)?,
+                close: row.get(4)?,
+                turnover: row.get(5)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<Bar>>>()?)
+    }
+
+    /// 以最近两根K线构造报价
+    pub fn quote(&self, symbol: &str) -> Result<Quote> {
+        let bars = self.bars(symbol)?;
+        let (prev, last) = match bars.as_slice() {
+            [.., prev, last] => (prev.clone(), last.clone()),
+            [last] => (Bar::default(), last.clone()),
+            [] => bail!("{} 无本地行情数据，请先同步", symbol),
+        };
+
+        Ok(Quote {
+            symbol: symbol.to_string(),
+            name: self.investment(symbol)?.map(|invest| invest.name).unwrap_or_default(),
+            now: last.close,
+            close: prev.close,
+            open: last.open,
+            high: last.high,
+            low: last.low,
+            turnover: last.turnover,
+            currency: Currency::from_symbol(symbol),
+            // 日K线以收盘时间计
+            time: NaiveTime::from_hms_opt(15, 0, 0)
+                .and_then(|close| calendar::localize(&Exchange::Sse, last.date.and_time(close))),
+            ..Default::default()
+        })
+    }
+
+    pub fn save_profile(&mut self, symbol: &str, profile: &Profile) -> Result<usize> {
+        self.save_records(symbol, "profile", &[(String::new(), String::new(), profile)])
+    }
+
+    pub fn profile(&self, symbol: &str) -> Result<Profile> {
+        match self.records::<Profile>(symbol, "profile")?.pop() {
+            Some(profile) => Ok(profile),
+            None => bail!("{} 无本地公司信息，请先同步", symbol),
+        }
+    }
+
+    pub fn save_financials(&mut self, symbol: &str, financials: &[Financial]) -> Result<usize> {
+        let records = financials.iter().map(|f| (f.date.to_string(), f.date.to_string(), f)).collect::<Vec<_>>();
+        self.save_records(symbol, "financial", &records)
+    }
+
+    pub fn financials(&self, symbol: &str) -> Result<Vec<Financial>> {
+        self.records(symbol, "financial")
+    }
+
+    pub fn save_balance_sheets(&mut self, symbol: &str, sheets: &[BalanceSheet]) -> Result<usize> {
+        let records = sheets.iter().map(|s| (s.date.to_string(), s.date.to_string(), s)).collect::<Vec<_>>();
+        self.save_records(symbol, "balance", &records)
+    }
+
+    pub fn balance_sheets(&self, symbol: &str) -> Result<Vec<BalanceSheet>> {
+        self.records(symbol, "balance")
+    }
+
+    pub fn save_income_statements(&mut self, symbol: &str, incomes: &[IncomeStatement]) -> Result<usize> {
+        let records = incomes.iter().map(|s| (s.date.to_string(), s.date.to_string(), s)).collect::<Vec<_>>();
+        self.save_records(symbol, "income", &records)
+    }
+
+    pub fn income_statements(&self, symbol: &str) -> Result<Vec<IncomeStatement>> {
+        self.records(symbol, "income")
+    }
+
+    pub fn save_cash_flows(&mut self, symbol: &str, flows: &[CashFlow]) -> Result<usize> {
+        let records = flows.iter().map(|s| (s.date.to_string(), s.date.to_string(), s)).collect::<Vec<_>>();
+        self.save_records(symbol, "cashflow", &records)
+    }
+
+    pub fn cash_flows(&self, symbol: &str) -> Result<Vec<CashFlow>> {
+        self.records(symbol, "cashflow")
+    }
+
+    pub fn save_structures(&mut self, symbol: &str, structures: &[Structure]) -> Result<usize> {
+        let records = structures.iter().map(|s| (s.date.to_string(), s.date.to_string(), s)).collect::<Vec<_>>();
+        self.save_records(symbol, "structure", &records)
+    }
+
+    pub fn structures(&self, symbol: &str) -> Result<Vec<Structure>> {
+        self.records(symbol, "structure")
+    }
+
+    pub fn save_capitals(&mut self, symbol: &str, capitals: &[Capital]) -> Result<usize> {
+        let records = capitals.iter().map(|c| (c.date.to_string(), c.date.to_string(), c)).collect::<Vec<_>>();
+        self.save_records(symbol, "capital", &records)
+    }
+
+    pub fn capitals(&self, symbol: &str) -> Result<Vec<Capital>> {
+        self.records(symbol, "capital")
+    }
+
+    /// 同日可能有多批解禁 以日期及类型为键
+    pub fn save_unlocks(&mut self, symbol: &str, unlocks: &[Unlock]) -> Result<usize> {
+        let records =
+            unlocks.iter().map(|u| (format!("{}_{}", u.date, u.kind), u.date.to_string(), u)).collect::<Vec<_>>();
+        self.save_records(symbol, "unlock", &records)
+    }
+
+    pub fn unlocks(&self, symbol: &str) -> Result<Vec<Unlock>> {
+        self.records(symbol, "unlock")
+    }
+
+    /// 同日公告可能有多次分红 以公告日及除权除息日为键 未实施的除权除息日为 -
+    ///
+    /// 仅以公告日为键的旧记录及实施前的记录一并删除 与写入在同一事务中
+    pub fn save_dividends(&mut self, symbol: &str, dividends: &[Dividend]) -> Result<usize> {
+        let key = |d: &Dividend| format!("{}_{}", d.date, d.date_dividend.map_or("-".to_string(), |d| d.to_string()));
+        let tx = self.conn.transaction()?;
+        for d in dividends.iter() {
+            let pending = format!("{}_-", d.date);
+            tx.execute(
+                "DELETE FROM records WHERE symbol = ?1 AND kind = 'dividend' AND key IN (?2, ?3) AND key != ?4",
+                params![symbol, d.date.to_string(), pending, key(d)],
+            )?;
+        }
+        let records = dividends.iter().map(|d| (key(d), d.date.to_string(), d)).collect::<Vec<_>>();
+        let added = insert_records(&tx, symbol, "dividend", &records)?;
+        tx.commit()?;
+
+        Ok(added)
+    }
+
+    pub fn dividends(&self, symbol: &str) -> Result<Vec<Dividend>> {
+        self.records(symbol, "dividend")
+    }
+
+    pub fn save_presses(&mut self, symbol: &str, presses: &[Press]) -> Result<usize> {
+        let records = presses.iter().map(|p| (p.url.to_string(), p.date.to_string(), p)).collect::<Vec<_>>();
+        self.save_records(symbol, "press", &records)
+    }
+
+    pub fn presses(&self, symbol: &str) -> Result<Vec<Press>> {
+        self.records(symbol, "press")
+    }
+
+    /// 公告监控已处理的公告链接
+    pub fn seen_presses(&self, symbol: &str) -> Result<HashSet<String>> {
+        Ok(self.records::<Press>(symbol, "press_seen")?.into_iter().map(|p| p.url).collect())
+    }
+
+    pub fn save_seen_presses(&mut self, symbol: &str, presses: &[Press]) -> Result<usize> {
+        let records = presses.iter().map(|p| (p.url.to_string(), p.date.to_string(), p)).collect::<Vec<_>>();
+        self.save_records(symbol, "press_seen", &records)
+    }
+
+    /// 公告监控开始日期 未开始时为 None 首次监控仅记录现有公告不提醒
+    pub fn press_watch_since(&self, symbol: &str) -> Result<Option<NaiveDate>> {
+        Ok(self.records(symbol, "press_watch")?.into_iter().next())
+    }
+
+    pub fn save_press_watch_since(&mut self, symbol: &str, date: NaiveDate) -> Result<usize> {
+        self.save_records(symbol, "press_watch", &[("since".to_string(), date.to_string(), &date)])
+    }
+
+    /// 最近一次在线获取的汇率 供离线换算
+    pub fn save_rates(&mut self, rates: &Rates, date: NaiveDate) -> Result<usize> {
+        self.save_records("FX", "rates", &[("latest".to_string(), date.to_string(), rates)])
+    }
+
+    pub fn rates(&self) -> Result<Option<Rates>> {
+        Ok(self.records("FX", "rates")?.into_iter().next())
+    }
+
+    /// records: (key, date, data) 已存在的覆盖 返回新增条数
+    fn save_records<T: Serialize>(
+        &mut self,
+        symbol: &str,
+        kind: &str,
+        records: &[(String, String, &T)],
+    ) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let added = insert_records(&tx, symbol, kind, records)?;
+        tx.commit()?;
+
+        Ok(added)
+    }
+
+    /// 按日期降序 与数据源一致
+    fn records<T: DeserializeOwned>(&self, symbol: &str, kind: &str) -> Result<Vec<T>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT data FROM records WHERE symbol = ?1 AND kind = ?2 ORDER BY date DESC, key DESC")?;
+        let rows = stmt.query_map(params![symbol, kind], |row| row.get::<_, String>(0))?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(serde_json::from_str(&row?)?);
+        }
+
+        Ok(records)
+    }
+}
+
+/// 在调用方事务中写入 返回新增条数
+fn insert_records<T: Serialize>(
+    tx: &Transaction,
+    symbol: &str,
+    kind: &str,
+    records: &[(String, String, &T)],
+) -> Result<usize> {
+    let mut added = 0;
+    for (key, date, data) in records.iter() {
+        let exists = tx
+            .query_row(
+                "SELECT 1 FROM records WHERE symbol = ?1 AND kind = ?2 AND key = ?3",
+                params![symbol, kind, key],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !exists {
+            added += 1;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO records (symbol, kind, key, date, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![symbol, kind, key, date, serde_json::to_string(data)?],
+        )?;
+    }
+
+    Ok(added)
+}
+
+/// 数据目录 优先取环境变量 RAINS_HOME 默认 ~/.rains
+pub fn data_dir() -> Result<PathBuf> {
+    if let Ok(dir) = env::var("RAINS_HOME") {
+        return Ok(PathBuf::from(dir));
+    }
+    match env::var("HOME").or_else(|_| env::var("USERPROFILE")) {
+        Ok(home) => Ok(Path::new(&home).join(".rains")),
+        Err(_) => bail!("无法确定用户目录，请设置 RAINS_HOME"),
+    }
+}
+
+/// 读取自选列表 ~/.rains/watchlist/<name> 代码以换行或 , 分隔
+pub fn watchlist(name: &str) -> Result<Vec<String>> {
+    let path = data_dir()?.join("watchlist").join(name);
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(content
+            .split([',', '\n'])
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty() && !s.starts_with('#'))
+            .collect()),
+        Err(err) => bail!("读取自选列表 {} 失败，{}", path.display(), err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invest::{calendar::parse_date, sector::Classification};
+
+    fn store() -> Store {
+        Store::with_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_records() {
+        let mut store = store();
+        let capital = |date: &str, total: f64| Capital { date: parse_date(date).unwrap(), total, ..Default::default() };
+        assert_eq!(
+            store.save_capitals("SH601318", &[capital("2022-01-01", 1.0), capital("2021-01-01", 1.0)]).unwrap(),
+            2
+        );
+        // 已存在的覆盖不计入新增
+        assert_eq!(
+            store.save_capitals("SH601318", &[capital("2022-06-01", 3.0), capital("2022-01-01", 2.0)]).unwrap(),
+            1
+        );
+        let capitals = store.capitals("SH601318").unwrap();
+        assert_eq!(capitals.iter().map(|c| c.total).collect::<Vec<_>>(), vec![3.0, 2.0, 1.0]);
+        assert!(store.capitals("SZ000001").unwrap().is_empty());
+
+        let dividend = |date_dividend: &str| Dividend {
+            date: parse_date("2022-03-17").unwrap(),
+            date_dividend: parse_date(date_dividend),
+            money: 15.0,
+            ..Default::default()
+        };
+        assert_eq!(store.save_dividends("SH601318", &[dividend("--")]).unwrap(), 1);
+        // 实施后替换未实施的记录
+        assert_eq!(store.save_dividends("SH601318", &[dividend("2022-07-22")]).unwrap(), 1);
+        let dividends = store.dividends("SH601318").unwrap();
+        assert_eq!(dividends.len(), 1);
+        assert_eq!(dividends[0].date_dividend, parse_date("2022-07-22"));
+        assert_eq!(store.save_dividends("SH601318", &[dividend("2022-07-22")]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_press_watch() {
+        let mut store = store();
+        assert_eq!(store.press_watch_since("SH601318").unwrap(), None);
+        assert!(store.seen_presses("SH601318").unwrap().is_empty());
+
+        let press = Press { date: parse_date("2022-03-29").unwrap(), url: "a".to_string(), ..Default::default() };
+        store.save_seen_presses("SH601318", &[press]).unwrap();
+        store.save_press_watch_since("SH601318", parse_date("2022-04-01").unwrap()).unwrap();
+        assert!(store.seen_presses("SH601318").unwrap().contains("a"));
+        assert_eq!(store.press_watch_since("SH601318").unwrap(), parse_date("2022-04-01"));
+        assert_eq!(store.press_watch_since("SZ000001").unwrap(), None);
+    }
+
+    #[test]
+    fn test_sector_migration() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE investments (symbol TEXT PRIMARY KEY, code TEXT NOT NULL, name TEXT NOT NULL);
+            INSERT INTO investments VALUES ('SH601318', '601318', '中国平安');",
+        )
+        .unwrap();
+        let store = Store::with_connection(conn).unwrap();
+        let invest = store.investment("SH601318").unwrap().unwrap();
+        assert_eq!(invest.name, "中国平安");
+        assert_eq!(invest.sector, None);
+
+        // 旧版行业名称文本视为未同步
+        store.conn.execute("UPDATE investments SET sector = '保险'", []).unwrap();
+        assert_eq!(store.investment("SH601318").unwrap().unwrap().sector, None);
+
+        let sector = Classification { sw1: Some("非银金融".to_string()), ..Default::default() };
+        store.save_investment(&Investment { sector: Some(sector.clone()), ..invest }).unwrap();
+        assert_eq!(store.investment("SH601318").unwrap().unwrap().sector, Some(sector));
+        assert_eq!(store.search("非银").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,366 @@
+//! 页面表格解析 按表头或行标签定位 不依赖单元格位置 页面改版时返回 LayoutError
+
+use std::fmt;
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+
+/// 页面布局与预期不符 eg: 改版后表格、表头或行标签缺失
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutError {
+    pub table: String,
+    /// 缺失的表头或标签 为空时表格未找到
+    pub missing: Vec<String>,
+    /// 列数与表头不符的明细行 单元格以 | 连接
+    pub row: Option<String>,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(row) = &self.row {
+            write!(f, "页面布局已变化，表格 {} 的行 {} 与表头列数不符", self.table, row)
+        } else if self.missing.is_empty() {
+            write!(f, "页面布局已变化，未找到表格 {}", self.table)
+        } else {
+            write!(f, "页面布局已变化，表格 {} 缺少 {}", self.table, self.missing.join("、"))
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+#[derive(Debug, Clone)]
+struct Cell {
+    text: String,
+    colspan: usize,
+}
+
+/// 标签与值 按标签前缀取值 同名取首个
+#[derive(Debug, Default, Clone)]
+pub struct Record(Vec<(String, String)>);
+
+impl Record {
+    /// 未找到为空 eg: 实际发行量 匹配 实际发行量(万股)
+    pub fn get(&self, label: &str) -> &str {
+        self.0.iter().find(|(l, _)| l.starts_with(label)).map_or("", |(_, v)| v.as_str())
+    }
+
+    pub fn contains(&self, label: &str) -> bool {
+        self.0.iter().any(|(l, _)| l.starts_with(label))
+    }
+}
+
+/// 以某标签行开始的一段 eg: 每个截止日期下的股东总数及十大股东明细
+#[derive(Debug, Default, Clone)]
+pub struct Block {
+    /// 两列及以上的标签行 取首列为标签 末列为值
+    pub labels: Record,
+    /// 表头之后与表头列数相同的明细行
+    pub records: Vec<Record>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Table {
+    name: String,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// 全部匹配的表格 一个都未找到时为布局错误
+    pub fn select_all(content: &str, selector: &str) -> Result<Vec<Table>> {
+        let doc = Html::parse_document(content);
+        let tables = Selector::parse(selector).unwrap();
+        let tr = Selector::parse("tr").unwrap();
+        let td = Selector::parse("th, td").unwrap();
+        let tables = doc
+            .select(&tables)
+            .map(|table| Table {
+                name: selector.to_string(),
+                rows: table
+                    .select(&tr)
+                    .map(|tr| {
+                        tr.select(&td)
+                            .map(|td| Cell {
+                                text: cell_text(td),
+                                colspan: td.value().attr("colspan").and_then(|n| n.parse().ok()).unwrap_or(1),
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        if tables.is_empty() {
+            bail!(LayoutError { table: selector.to_string(), missing: vec![], row: None });
+        }
+
+        Ok(tables)
+    }
+
+    pub fn select(content: &str, selector: &str) -> Result<Table> {
+        Ok(Table::select_all(content, selector)?.remove(0))
+    }
+
+    /// 各行单元格文本
+    pub fn rows(&self) -> impl Iterator<Item = Vec<&str>> {
+        self.rows.iter().map(|row| row.iter().map(|c| c.text.as_str()).collect())
+    }
+
+    /// 首列为标签 其余为值 同名取首个 eg: 报表日期 | 2021-12-31 | 2020-12-31
+    pub fn labeled_rows(&self) -> Vec<(String, Vec<String>)> {
+        let mut rows: Vec<(String, Vec<String>)> = Vec::new();
+        for row in self.rows() {
+            if let Some((label, values)) = row.split_first().filter(|(_, values)| !values.is_empty()) {
+                let label = normalize_label(label);
+                if !rows.iter().any(|(l, _)| *l == label) {
+                    rows.push((label, values.iter().map(|v| v.to_string()).collect()));
+                }
+            }
+        }
+
+        rows
+    }
+
+    /// 标签与值成对排列 eg: 公司名称：| xx | 上市日期：| xx
+    pub fn fields(&self) -> Record {
+        let mut fields = Vec::new();
+        for row in self.rows() {
+            for pair in row.chunks(2) {
+                if let [label, value] = pair {
+                    if !label.is_empty() {
+                        fields.push((normalize_label(label), value.to_string()));
+                    }
+                }
+            }
+        }
+
+        Record(fields)
+    }
+
+    /// 按表头定位列 expected 均需存在 表头可为两层 eg: 分红方案 下分 送股 转增 派息
+    ///
+    /// 空行及 暂无 等占位行跳过 其余列数与表头不符时为布局错误
+    pub fn records(&self, expected: &[&str]) -> Result<Vec<Record>> {
+        for i in 0..self.rows.len() {
+            if let Some((header, skip)) = self.header(i, expected) {
+                let mut records = Vec::new();
+                for row in self.rows[i + skip..].iter().filter(|row| !is_placeholder(row)) {
+                    if row.len() != header.len() {
+                        bail!(self.mismatch(row));
+                    }
+                    records.push(Record(header.iter().cloned().zip(row.iter().map(|c| c.text.to_string())).collect()));
+                }
+                return Ok(records);
+            }
+        }
+
+        bail!(self.missing(expected))
+    }
+
+    /// 按 start 标签行分段 headers 为空时仅有标签行 否则需能找到表头
+    ///
+    /// 段内出现表头或明细后 至下一段前的非占位行列数需与表头相同
+    pub fn blocks(&self, start: &str, headers: &[&str]) -> Result<Vec<Block>> {
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut header: Option<Vec<String>> = None;
+        // 当前段已进入明细行
+        let mut detail = false;
+        let mut i = 0;
+        while i < self.rows.len() {
+            let row = &self.rows[i];
+            if let Some((h, skip)) = Some(headers).filter(|h| !h.is_empty()).and_then(|h| self.header(i, h)) {
+                header = Some(h);
+                detail = true;
+                i += skip;
+                continue;
+            }
+            i += 1;
+
+            let is_start = row.first().is_some_and(|c| normalize_label(&c.text) == start);
+            match (&header, blocks.last_mut()) {
+                (Some(header), Some(block)) if row.len() == header.len() => {
+                    let cells = row.iter().map(|c| c.text.to_string());
+                    block.records.push(Record(header.iter().cloned().zip(cells).collect()));
+                    detail = true;
+                    continue;
+                }
+                (Some(_), Some(_)) if detail && !is_start && !is_placeholder(row) => bail!(self.mismatch(row)),
+                _ => {}
+            }
+            if let [label, .., value] = row.as_slice() {
+                let label = normalize_label(&label.text);
+                if label == start {
+                    blocks.push(Block::default());
+                    detail = false;
+                }
+                if let Some(block) = blocks.last_mut() {
+                    block.labels.0.push((label, value.text.to_string()));
+                }
+            }
+        }
+
+        if blocks.is_empty() {
+            bail!(self.missing(&[start]));
+        }
+        if !headers.is_empty() && header.is_none() {
+            bail!(self.missing(headers));
+        }
+
+        Ok(blocks)
+    }
+
+    /// 校验行标签均存在 按前缀匹配
+    pub fn expect_labels(&self, expected: &[&str]) -> Result<()> {
+        let labels = self.labeled_rows();
+        let missing = expected
+            .iter()
+            .filter(|e| !labels.iter().any(|(l, _)| l.starts_with(*e)))
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            bail!(LayoutError { table: self.name.to_string(), missing, row: None });
+        }
+
+        Ok(())
+    }
+
+    /// 第 i 行起的表头及其行数 含合并单元格时与下一行组成两层表头
+    fn header(&self, i: usize, expected: &[&str]) -> Option<(Vec<String>, usize)> {
+        let row = &self.rows[i];
+        let matches = |header: &[String]| expected.iter().all(|e| header.iter().any(|h| h.starts_with(e)));
+        if let Some(next) = self.rows.get(i + 1).filter(|_| row.iter().any(|c| c.colspan > 1)) {
+            let mut subs = next.iter();
+            let header = row
+                .iter()
+                .flat_map(|c| match c.colspan {
+                    1 => vec![normalize_label(&c.text)],
+                    n => subs.by_ref().take(n).map(|s| normalize_label(&s.text)).collect(),
+                })
+                .collect::<Vec<_>>();
+            if matches(&header) {
+                return Some((header, 2));
+            }
+        }
+        let header = row.iter().map(|c| normalize_label(&c.text)).collect::<Vec<_>>();
+        if matches(&header) {
+            return Some((header, 1));
+        }
+
+        None
+    }
+
+    fn mismatch(&self, row: &[Cell]) -> LayoutError {
+        let row = row.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join("|");
+        LayoutError { table: self.name.to_string(), missing: vec![], row: Some(row) }
+    }
+
+    /// 表格中任一单元格都不匹配的标签 均能匹配时为全部 eg: 表头分散在多行
+    fn missing(&self, expected: &[&str]) -> LayoutError {
+        let labels = self.rows.iter().flatten().map(|c| normalize_label(&c.text)).collect::<Vec<_>>();
+        let mut missing = expected
+            .iter()
+            .filter(|e| !labels.iter().any(|l| l.starts_with(*e)))
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            missing = expected.iter().map(|e| e.to_string()).collect();
+        }
+
+        LayoutError { table: self.name.to_string(), missing, row: None }
+    }
+}
+
+/// 去除科目序号、加减前缀、末尾冒号及单位 eg: 五、净利润 / 减：营业成本 / 净资产收益率(%)
+pub fn normalize_label(label: &str) -> String {
+    static PREFIX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^([一二三四五六七八九十]+、|[(（][一二三四五六七八九十]+[)）]|加：|减：|其中：)").unwrap()
+    });
+    static UNIT: Lazy<Regex> = Lazy::new(|| Regex::new(r"[(（](元|%|元/股|万元|股|户)[)）]$").unwrap());
+    let label = label.split_whitespace().collect::<String>();
+    let label = PREFIX.replace(&label, "");
+    UNIT.replace(label.trim_end_matches(['：', ':']), "").replace('（', "(").replace('）', ")")
+}
+
+/// 空行或合并单元格的 暂无 占位行
+fn is_placeholder(row: &[Cell]) -> bool {
+    match row {
+        [cell] if cell.colspan > 1 && cell.text.contains("暂无") => true,
+        _ => row.iter().all(|c| c.text.is_empty()),
+    }
+}
+
+fn cell_text(td: ElementRef) -> String {
+    td.text().collect::<String>().trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let content = r#"<table id="sharebonus_1">
+            <thead><tr><th rowspan="2">公告日期</th><th colspan="3">分红方案(每10股)</th><th rowspan="2">除权除息日</th></tr>
+            <tr><th>送股(股)</th><th>转增(股)</th><th>派息(税前)(元)</th></tr></thead>
+            <tbody><tr><td>2022-07-15</td><td>0</td><td>0</td><td>15</td><td>2022-07-22</td></tr>
+            <tr><td colspan="5">暂无</td></tr></tbody></table>"#;
+        let table = Table::select(content, "#sharebonus_1").unwrap();
+        let records = table.records(&["公告日期", "送股", "转增", "派息", "除权除息日"]).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].get("派息"), "15");
+        assert_eq!(records[0].get("除权除息日"), "2022-07-22");
+        assert_eq!(records[0].get("红股上市日"), "");
+
+        let mismatched = content.replace(r#"<td colspan="5">暂无</td>"#, "<td>2021-07-15</td><td>12</td>");
+        let err = Table::select(&mismatched, "#sharebonus_1")
+            .unwrap()
+            .records(&["公告日期", "送股", "转增", "派息", "除权除息日"])
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<LayoutError>().unwrap().row.as_deref(), Some("2021-07-15|12"));
+
+        let err = table.records(&["公告日期", "股权登记日"]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LayoutError>(),
+            Some(&LayoutError {
+                table: "#sharebonus_1".to_string(),
+                missing: vec!["股权登记日".to_string()],
+                row: None
+            })
+        );
+        assert!(Table::select(content, "#Table1").unwrap_err().is::<LayoutError>());
+
+        let table = Table::select(
+            r#"<table id="Table1">
+            <tr><td>截止日期</td><td>2021-12-31</td></tr><tr><td>股东总数</td><td>217155</td></tr>
+            <tr><td>编号</td><td>股东名称</td><td>持股数量(股)</td></tr><tr><td>1</td><td>A</td><td>100</td></tr>
+            <tr><td>截止日期</td><td>2021-09-30</td></tr><tr><td>1</td><td>B</td><td>90</td></tr>
+            </table>"#,
+            "#Table1",
+        )
+        .unwrap();
+        let blocks = table.blocks("截止日期", &["股东名称", "持股数量"]).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].labels.get("股东总数"), "217155");
+        assert_eq!(blocks[1].labels.get("截止日期"), "2021-09-30");
+        assert_eq!(blocks[1].records[0].get("持股数量"), "90");
+        assert!(table.blocks("截止日期", &["股东名称", "股本性质"]).is_err());
+        assert!(table.expect_labels(&["截止日期", "股东总数"]).is_ok());
+        assert!(table.expect_labels(&["平均持股数"]).is_err());
+
+        let table = Table::select(
+            r#"<table id="Table1">
+            <tr><td>截止日期</td><td>2021-12-31</td></tr>
+            <tr><td>编号</td><td>股东名称</td><td>持股数量(股)</td></tr><tr><td>1</td><td>A</td><td>100</td></tr>
+            <tr><td></td></tr><tr><td>2</td><td>B</td></tr>
+            </table>"#,
+            "#Table1",
+        )
+        .unwrap();
+        let err = table.blocks("截止日期", &["股东名称", "持股数量"]).unwrap_err();
+        assert_eq!(err.downcast_ref::<LayoutError>().unwrap().row.as_deref(), Some("2|B"));
+
+        assert_eq!(normalize_label("减：营业成本"), "营业成本");
+        assert_eq!(normalize_label("所有者权益（或股东权益）合计"), "所有者权益(或股东权益)合计");
+    }
+}